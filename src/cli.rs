@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::export::ExportFormat;
+
+const AFTER_HELP: &str = "\
+Environment:
+  WORKWATCH_USERNAME    Name shown in the app and in webhook messages
+  WORKWATCH_WEBHOOK     Discord or Slack webhook URL; messages are disabled when unset
+  WORKWATCH_BOT_NAME    Name the webhook messages are posted under
+  WORKWATCH_EXPORT_DIR  Directory session Markdown exports are written to
+  WORKWATCH_DATA_DIR    Directory the session, history and project totals are stored in
+  WORKWATCH_DRY_RUN     Same as --dry-run: \"1\" for the default file, \"-\" for stderr, or a path
+
+Settings are also read from ~/.config/workwatch/workwatch.toml. Flags take precedence over
+environment variables, which take precedence over the config file.";
+
+/// Terminal-based time tracking with activity logs and webhook notifications.
+#[derive(Parser)]
+#[command(version, about, after_help = AFTER_HELP)]
+pub struct Cli {
+    /// Name shown in the app and in webhook messages
+    #[arg(long)]
+    pub username: Option<String>,
+
+    /// Discord or Slack webhook URL to post clock in/out messages to
+    #[arg(long)]
+    pub webhook: Option<String>,
+
+    /// Name the webhook messages are posted under
+    #[arg(long)]
+    pub bot_name: Option<String>,
+
+    /// Write webhook payloads to PATH (or stderr for "-") instead of posting them. Without
+    /// PATH they go to dry-run.jsonl in the data directory
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    pub dry_run: Option<Option<PathBuf>>,
+
+    /// Clock in right away instead of opening on the Menu
+    #[arg(long)]
+    pub start_working: bool,
+
+    /// Draw a single status line instead of the full layout, for small panes
+    #[arg(long)]
+    pub compact: bool,
+
+    /// Print every recorded session as CSV or JSON and exit instead of starting the TUI
+    #[arg(long, value_name = "FORMAT")]
+    pub export: Option<ExportFormat>,
+
+    /// On exit, print the session that was clocked out as JSON to stdout, for wrapper scripts
+    #[arg(long)]
+    pub json_summary: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Non-interactive actions on the saved session. Without one, the TUI starts.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Clock in, post the clock-in message and exit. The session keeps counting until it is
+    /// clocked out, here or in the TUI
+    #[command(name = "clockin")]
+    ClockIn,
+
+    /// Clock out of the saved session, post the clock-out message and exit
+    #[command(name = "clockout")]
+    ClockOut {
+        /// Added as a final log before clocking out
+        summary: Option<String>,
+    },
+}
@@ -0,0 +1,696 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset, Local, NaiveTime, Utc};
+use chrono_tz::Tz;
+use ratatui::style::Color;
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{
+    cli::Cli,
+    keymap::{Action, KeyNames, Keymap},
+    template,
+    time_format::TimeFormat,
+};
+
+/// Faster redraws than this would only burn CPU.
+const MIN_REFRESH_MS: u64 = 50;
+
+/// Shown above the Menu's welcome line when `banner` is on without a `banner_file`.
+const DEFAULT_BANNER: &str = include_str!("banner.txt");
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFlavor {
+    Discord,
+    Slack,
+}
+
+impl WebhookFlavor {
+    /// Guesses the flavor from the webhook host, treating anything that isn't Slack as Discord.
+    pub fn detect(webhook_url: &str) -> Self {
+        let host = Url::parse(webhook_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string));
+
+        match host {
+            Some(host) if host == "hooks.slack.com" => WebhookFlavor::Slack,
+            _ => WebhookFlavor::Discord,
+        }
+    }
+}
+
+/// The URLs in a `webhook_url` setting, which lists several destinations separated by commas.
+pub fn webhook_urls(webhook_url: &str) -> impl Iterator<Item = &str> {
+    webhook_url
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+}
+
+/// One webhook URL or a list of them, e.g. `"https://…"` or `["https://…", "https://…"]`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WebhookUrls {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The time zone dates and times in webhook messages are written in.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Timezone {
+    #[default]
+    Local,
+    Utc,
+    /// An IANA zone such as `Europe/Berlin`.
+    Named(Tz),
+}
+
+impl Timezone {
+    pub fn convert(self, at: DateTime<Local>) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Local => at.fixed_offset(),
+            Timezone::Utc => at.with_timezone(&Utc).fixed_offset(),
+            Timezone::Named(tz) => at.with_timezone(&tz).fixed_offset(),
+        }
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        match name.to_lowercase().as_str() {
+            "local" => Ok(Timezone::Local),
+            "utc" => Ok(Timezone::Utc),
+            _ => name
+                .parse()
+                .map(Timezone::Named)
+                .map_err(|_| format!("unknown time zone {:?}", name)),
+        }
+    }
+}
+
+/// Which mentions in usernames and logs may ping people once posted. Anything not allowed is
+/// escaped so it shows up as plain text.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mentions {
+    /// No mentions at all, including `@everyone` and `@here`.
+    #[default]
+    Off,
+    /// Mentions of single users, but not roles, `@everyone` or `@here`.
+    Users,
+    /// Everything, exactly as written.
+    All,
+}
+
+/// When the session export is attached to the clock-out message as a Markdown file. Only Discord
+/// webhooks take attachments.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachExport {
+    Never,
+    /// Only when the log list is too long for the message itself.
+    #[default]
+    Long,
+    Always,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FileConfig {
+    username: Option<String>,
+    webhook_url: Option<WebhookUrls>,
+    webhook_flavor: Option<WebhookFlavor>,
+    mentions: Option<Mentions>,
+    attach_export: Option<AttachExport>,
+    bot_name: Option<String>,
+    export_dir: Option<PathBuf>,
+    status_file: Option<PathBuf>,
+    status_line_file: Option<PathBuf>,
+    history_retention_days: Option<u64>,
+    projects: Vec<String>,
+    project_webhooks: HashMap<String, String>,
+    idle_timeout_minutes: Option<u64>,
+    heartbeat_minutes: Option<u64>,
+    notify_clock_in: Option<bool>,
+    notify_clock_out: Option<bool>,
+    notify_pause: Option<bool>,
+    notify_new_day: Option<bool>,
+    min_session_secs: Option<u64>,
+    webhook_timeout_secs: Option<u64>,
+    daily_goal_minutes: Option<u64>,
+    word_goal: Option<usize>,
+    time_format: Option<TimeFormat>,
+    timezone: Option<Timezone>,
+    sound: Option<bool>,
+    compact: Option<bool>,
+    banner: Option<bool>,
+    banner_file: Option<PathBuf>,
+    start_working: Option<bool>,
+    summary_secs: Option<u64>,
+    recent_logs: Option<usize>,
+    log_level: Option<String>,
+    focus_lock_minutes: Option<u64>,
+    confirm_clock_out: Option<bool>,
+    clock_out_at: Option<String>,
+    refresh_ms: Option<u64>,
+    idle_refresh_ms: Option<u64>,
+    theme: FileTheme,
+    pomodoro: FilePomodoro,
+    messages: MessageTemplates,
+    keys: HashMap<Action, KeyNames>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FileTheme {
+    preset: Option<ThemePreset>,
+    text: Option<String>,
+    accent: Option<String>,
+    selected: Option<String>,
+    paused: Option<String>,
+    tag: Option<String>,
+    border_menu: Option<String>,
+    border_working: Option<String>,
+    border_paused: Option<String>,
+    border_logs: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FilePomodoro {
+    work_minutes: Option<u64>,
+    break_minutes: Option<u64>,
+    notify: Option<bool>,
+}
+
+/// Where webhook payloads go instead of the network in dry-run mode.
+#[derive(Clone)]
+pub enum DryRun {
+    Stderr,
+    File(PathBuf),
+}
+
+impl DryRun {
+    fn from_arg(arg: Option<PathBuf>) -> Self {
+        match arg {
+            Some(path) if path.as_os_str() == "-" => DryRun::Stderr,
+            Some(path) => DryRun::File(path),
+            None => DryRun::File(data_dir().join("dry-run.jsonl")),
+        }
+    }
+}
+
+/// Custom webhook titles. Unset templates keep the built-in wording.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct MessageTemplates {
+    pub clock_in: Option<String>,
+    pub clock_out: Option<String>,
+    /// Put in front of the clock in/out titles; an empty string leaves them bare.
+    pub clock_in_icon: Option<String>,
+    pub clock_out_icon: Option<String>,
+}
+
+impl MessageTemplates {
+    pub fn clock_in_icon(&self) -> &str {
+        self.clock_in_icon.as_deref().unwrap_or("🟢")
+    }
+
+    pub fn clock_out_icon(&self) -> &str {
+        self.clock_out_icon.as_deref().unwrap_or("🔴")
+    }
+
+    fn validate(&self) {
+        let templates = [("clock_in", &self.clock_in), ("clock_out", &self.clock_out)];
+
+        for (name, template) in templates {
+            let Some(template) = template else {
+                continue;
+            };
+
+            for placeholder in template::unknown_placeholders(template) {
+                eprintln!(
+                    "WorkWatch Warning: unknown placeholder {{{}}} in [messages] {}! It will be left as written.",
+                    placeholder, name
+                );
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreset {
+    #[default]
+    Dark,
+    Light,
+}
+
+pub struct Theme {
+    pub text: Color,
+    pub accent: Color,
+    pub selected: Color,
+    pub paused: Color,
+    pub tag: Color,
+    pub border_menu: Color,
+    pub border_working: Color,
+    pub border_paused: Color,
+    pub border_logs: Color,
+}
+
+impl Theme {
+    pub fn preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Dark => Theme {
+                text: Color::Reset,
+                accent: Color::Blue,
+                selected: Color::LightGreen,
+                paused: Color::Yellow,
+                tag: Color::Cyan,
+                border_menu: Color::Reset,
+                border_working: Color::Green,
+                border_paused: Color::Yellow,
+                border_logs: Color::Blue,
+            },
+            // Darker shades that stay readable on a white background.
+            ThemePreset::Light => Theme {
+                text: Color::Black,
+                accent: Color::Blue,
+                selected: Color::Rgb(0, 128, 0),
+                paused: Color::Rgb(176, 112, 0),
+                tag: Color::Magenta,
+                border_menu: Color::DarkGray,
+                border_working: Color::Rgb(0, 128, 0),
+                border_paused: Color::Rgb(176, 112, 0),
+                border_logs: Color::Blue,
+            },
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::preset(ThemePreset::Dark)
+    }
+}
+
+/// Which events post a webhook message, for tuning how chatty the bot is.
+pub struct Notifications {
+    pub clock_in: bool,
+    pub clock_out: bool,
+    pub pause: bool,
+    /// Posted when a running session carries on past midnight.
+    pub new_day: bool,
+}
+
+pub struct PomodoroConfig {
+    pub work_length: Duration,
+    pub break_length: Duration,
+    pub notify: bool,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        PomodoroConfig {
+            work_length: Duration::from_secs(25 * 60),
+            break_length: Duration::from_secs(5 * 60),
+            notify: false,
+        }
+    }
+}
+
+pub struct Config {
+    pub username: String,
+    /// Every destination, separated by commas.
+    pub webhook_url: String,
+    /// Overrides the flavor detected from each URL's host.
+    pub webhook_flavor: Option<WebhookFlavor>,
+    pub mentions: Mentions,
+    pub attach_export: AttachExport,
+    pub bot_name: String,
+    pub export_dir: PathBuf,
+    /// Where the session, history and project totals are stored.
+    pub data_dir: PathBuf,
+    /// Where the current state is written as JSON for dashboards; off when unset.
+    pub status_file: Option<PathBuf>,
+    /// Where a one-line summary is written for status bars; off when unset.
+    pub status_line_file: Option<PathBuf>,
+    /// Sessions older than this many days are moved to the history archive on startup.
+    pub history_retention_days: Option<u64>,
+    pub projects: Vec<String>,
+    pub project_webhooks: HashMap<String, String>,
+    pub idle_timeout: Option<Duration>,
+    pub heartbeat_interval: Option<Duration>,
+    pub notify: Notifications,
+    /// Sessions shorter than this post neither a clock-in nor a clock-out message.
+    pub min_session: Option<Duration>,
+    /// How long a single webhook request may take before it counts as failed.
+    pub webhook_timeout: Duration,
+    pub daily_goal: Option<Duration>,
+    /// Words to write across a session's logs, for using the logs as a writing tracker.
+    pub word_goal: Option<usize>,
+    /// Overrides both the on-screen clock and the verbose webhook durations when set.
+    pub time_format: Option<TimeFormat>,
+    /// Applied to the dates and times written into webhook messages.
+    pub timezone: Timezone,
+    pub sound: bool,
+    pub compact: bool,
+    /// Art shown above the welcome line in the Menu; none when unset.
+    pub banner: Option<String>,
+    /// Clocks in on launch instead of opening on the Menu.
+    pub start_working: bool,
+    /// How long the summary screen stays up after clocking out; skipped when unset.
+    pub summary_duration: Option<Duration>,
+    /// How many of the latest logs the Working view previews under the timer.
+    pub recent_logs: usize,
+    pub focus_lock: Option<Duration>,
+    /// Asks before clocking out, showing what the clock-out message will contain.
+    pub confirm_clock_out: bool,
+    /// A time of day that ends any session started before it.
+    pub clock_out_at: Option<NaiveTime>,
+    pub dry_run: Option<DryRun>,
+    /// How much goes into `workwatch.log`, e.g. `info` or `debug`; nothing when unset, unless
+    /// `RUST_LOG` is.
+    pub log_level: Option<String>,
+    /// How often the screen is redrawn while clocked in, and outside a session.
+    pub refresh: Duration,
+    pub idle_refresh: Duration,
+    pub theme: Theme,
+    pub pomodoro: PomodoroConfig,
+    pub messages: MessageTemplates,
+    pub keymap: Keymap,
+}
+
+impl Config {
+    /// Resolves every setting from the command line, then the environment, then
+    /// `workwatch.toml`, then the defaults.
+    pub fn load(cli: Cli) -> Self {
+        Self::from_file(cli, load_file())
+    }
+
+    /// The settings from the command line and environment alone, as if `workwatch.toml` were
+    /// missing.
+    #[cfg(test)]
+    pub fn without_file(cli: Cli) -> Self {
+        Self::from_file(cli, FileConfig::default())
+    }
+
+    fn from_file(cli: Cli, file: FileConfig) -> Self {
+        let default_theme = Theme::preset(file.theme.preset.unwrap_or_default());
+        let default_pomodoro = PomodoroConfig::default();
+
+        file.messages.validate();
+
+        let username = match cli
+            .username
+            .or_else(|| env::var("WORKWATCH_USERNAME").ok())
+            .or(file.username)
+        {
+            Some(username) => username,
+            None => {
+                eprintln!(
+                    "WorkWatch Warning: WORKWATCH_USERNAME not found! Will default to Anonymous."
+                );
+                "Anonymous".to_string()
+            }
+        };
+
+        let webhook_url = match cli
+            .webhook
+            .or_else(|| env::var("WORKWATCH_WEBHOOK").ok())
+            .or(file.webhook_url.map(|urls| match urls {
+                WebhookUrls::One(url) => url,
+                WebhookUrls::Many(urls) => urls.join(", "),
+            })) {
+            Some(webhook) => webhook,
+            None => {
+                eprintln!(
+                    "WorkWatch Warning: WORKWATCH_WEBHOOK not found! Will not be able to post messages to discord!"
+                );
+                "".to_string()
+            }
+        };
+
+        Config {
+            username,
+            webhook_flavor: file.webhook_flavor,
+            webhook_url,
+            mentions: file.mentions.unwrap_or_default(),
+            attach_export: file.attach_export.unwrap_or_default(),
+            bot_name: cli
+                .bot_name
+                .or_else(|| env::var("WORKWATCH_BOT_NAME").ok())
+                .or(file.bot_name)
+                .unwrap_or_else(|| "WorkWatch".to_string()),
+            export_dir: env::var("WORKWATCH_EXPORT_DIR")
+                .ok()
+                .map(PathBuf::from)
+                .or(file.export_dir)
+                .unwrap_or_else(|| PathBuf::from(".")),
+            data_dir: data_dir(),
+            status_file: file.status_file,
+            status_line_file: file.status_line_file,
+            history_retention_days: file.history_retention_days.filter(|&days| days > 0),
+            projects: file.projects,
+            project_webhooks: file.project_webhooks,
+            idle_timeout: file
+                .idle_timeout_minutes
+                .filter(|&minutes| minutes > 0)
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            time_format: file.time_format,
+            timezone: file.timezone.unwrap_or_default(),
+            sound: file.sound.unwrap_or(false),
+            compact: cli.compact || file.compact.unwrap_or(false),
+            banner: load_banner(file.banner, file.banner_file),
+            start_working: cli.start_working || file.start_working.unwrap_or(false),
+            summary_duration: Some(file.summary_secs.unwrap_or(5))
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs),
+            confirm_clock_out: file.confirm_clock_out.unwrap_or(false),
+            clock_out_at: file.clock_out_at.and_then(|time| parse_time(&time)),
+            recent_logs: file.recent_logs.unwrap_or(3),
+            focus_lock: file
+                .focus_lock_minutes
+                .filter(|&minutes| minutes > 0)
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            dry_run: cli
+                .dry_run
+                .or_else(|| match env::var("WORKWATCH_DRY_RUN").ok()?.as_str() {
+                    "" | "0" | "false" => None,
+                    "1" | "true" => Some(None),
+                    path => Some(Some(PathBuf::from(path))),
+                })
+                .map(DryRun::from_arg),
+            log_level: file.log_level,
+            refresh: Duration::from_millis(file.refresh_ms.unwrap_or(1_000).max(MIN_REFRESH_MS)),
+            idle_refresh: Duration::from_millis(
+                file.idle_refresh_ms.unwrap_or(5_000).max(MIN_REFRESH_MS),
+            ),
+            heartbeat_interval: file
+                .heartbeat_minutes
+                .filter(|&minutes| minutes > 0)
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            notify: Notifications {
+                clock_in: file.notify_clock_in.unwrap_or(true),
+                clock_out: file.notify_clock_out.unwrap_or(true),
+                pause: file.notify_pause.unwrap_or(true),
+                new_day: file.notify_new_day.unwrap_or(false),
+            },
+            webhook_timeout: Duration::from_secs(file.webhook_timeout_secs.unwrap_or(10).max(1)),
+            min_session: file
+                .min_session_secs
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs),
+            daily_goal: file
+                .daily_goal_minutes
+                .filter(|&minutes| minutes > 0)
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            word_goal: file.word_goal.filter(|&words| words > 0),
+            theme: Theme {
+                text: parse_color(file.theme.text, default_theme.text),
+                accent: parse_color(file.theme.accent, default_theme.accent),
+                selected: parse_color(file.theme.selected, default_theme.selected),
+                paused: parse_color(file.theme.paused, default_theme.paused),
+                tag: parse_color(file.theme.tag, default_theme.tag),
+                border_menu: parse_color(file.theme.border_menu, default_theme.border_menu),
+                border_working: parse_color(
+                    file.theme.border_working,
+                    default_theme.border_working,
+                ),
+                border_paused: parse_color(file.theme.border_paused, default_theme.border_paused),
+                border_logs: parse_color(file.theme.border_logs, default_theme.border_logs),
+            },
+            pomodoro: PomodoroConfig {
+                work_length: file
+                    .pomodoro
+                    .work_minutes
+                    .map_or(default_pomodoro.work_length, |minutes| {
+                        Duration::from_secs(minutes * 60)
+                    }),
+                break_length: file
+                    .pomodoro
+                    .break_minutes
+                    .map_or(default_pomodoro.break_length, |minutes| {
+                        Duration::from_secs(minutes * 60)
+                    }),
+                notify: file.pomodoro.notify.unwrap_or(default_pomodoro.notify),
+            },
+            messages: file.messages,
+            keymap: Keymap::with_overrides(file.keys),
+        }
+    }
+}
+
+pub fn config_dir() -> PathBuf {
+    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+
+    PathBuf::from(home).join(".config").join("workwatch")
+}
+
+pub fn data_dir() -> PathBuf {
+    match env::var("WORKWATCH_DATA_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => config_dir(),
+    }
+}
+
+fn config_path() -> PathBuf {
+    config_dir().join("workwatch.toml")
+}
+
+/// Writes one setting changed on the Settings screen back to `workwatch.toml`, keeping
+/// everything else in the file, comments included, as it was.
+pub fn save_setting(key: &str, value: &str) -> io::Result<()> {
+    let path = config_path();
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error),
+    };
+
+    let contents = with_setting(&contents, key, value)
+        .map_err(|error| io::Error::new(ErrorKind::InvalidData, error.message().to_string()))?;
+
+    fs::create_dir_all(config_dir())?;
+    fs::write(path, contents)
+}
+
+/// `contents` with `key` set to `value`. A comma-separated `webhook_url` is written as an
+/// array, and a comment after the old value is kept.
+fn with_setting(contents: &str, key: &str, value: &str) -> Result<String, toml_edit::TomlError> {
+    let mut document = contents.parse::<toml_edit::DocumentMut>()?;
+    let urls = webhook_urls(value).collect::<Vec<&str>>();
+
+    let mut value = match urls[..] {
+        [_, _, ..] if key == "webhook_url" => urls.into_iter().collect(),
+        _ => toml_edit::Value::from(value),
+    };
+
+    if let Some(old) = document.get(key).and_then(toml_edit::Item::as_value) {
+        *value.decor_mut() = old.decor().clone();
+    }
+
+    document[key] = toml_edit::Item::Value(value);
+    Ok(document.to_string())
+}
+
+fn load_file() -> FileConfig {
+    let path = config_path();
+
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return FileConfig::default();
+    };
+
+    match toml::from_str(&contents) {
+        Ok(file) => file,
+        Err(error) => {
+            eprintln!(
+                "WorkWatch Warning: could not parse {}! Ignoring it. ({})",
+                path.display(),
+                error.message()
+            );
+            FileConfig::default()
+        }
+    }
+}
+
+/// The Menu banner: the art in `path` when given, otherwise the built-in one. Setting a path turns
+/// the banner on unless `enabled` says otherwise.
+fn load_banner(enabled: Option<bool>, path: Option<PathBuf>) -> Option<String> {
+    if !enabled.unwrap_or(path.is_some()) {
+        return None;
+    }
+
+    let Some(path) = path else {
+        return Some(DEFAULT_BANNER.to_string());
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(art) => Some(art),
+        Err(error) => {
+            eprintln!(
+                "WorkWatch Warning: could not read banner {}! Using the default. ({})",
+                path.display(),
+                error
+            );
+            Some(DEFAULT_BANNER.to_string())
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    let parsed = NaiveTime::parse_from_str(time, "%H:%M").ok();
+
+    if parsed.is_none() {
+        eprintln!(
+            "WorkWatch Warning: invalid clock_out_at {:?}, expected HH:MM! Ignoring it.",
+            time
+        );
+    }
+
+    parsed
+}
+
+fn parse_color(value: Option<String>, default: Color) -> Color {
+    match value {
+        Some(name) => name.parse().unwrap_or_else(|_| {
+            eprintln!(
+                "WorkWatch Warning: unknown theme color {:?}! Using the default.",
+                name
+            );
+            default
+        }),
+        None => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saving_a_setting_leaves_the_rest_of_the_file_alone() {
+        let contents =
+            "# my settings\nusername = \"Ada\" # shown in messages\n\n[keys]\nquit = \"x\"\n";
+
+        assert_eq!(
+            with_setting(contents, "username", "Grace").unwrap(),
+            "# my settings\nusername = \"Grace\" # shown in messages\n\n[keys]\nquit = \"x\"\n"
+        );
+        assert_eq!(
+            with_setting("", "webhook_url", "https://a.example, https://b.example").unwrap(),
+            "webhook_url = [\"https://a.example\", \"https://b.example\"]\n"
+        );
+    }
+}
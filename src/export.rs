@@ -0,0 +1,140 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::history::SessionRecord;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// One exported session, flattened to the columns a timesheet needs.
+#[derive(Serialize)]
+struct Row {
+    date: String,
+    clock_in: String,
+    clock_out: String,
+    total_seconds: u64,
+    log_count: usize,
+}
+
+impl Row {
+    fn from_record(record: &SessionRecord) -> Self {
+        Row {
+            date: record.clocked_in_at.format("%Y-%m-%d").to_string(),
+            clock_in: record.clocked_in_at.to_rfc3339(),
+            clock_out: record.clocked_out_at.to_rfc3339(),
+            total_seconds: record.seconds,
+            log_count: record.logs.len(),
+        }
+    }
+}
+
+pub fn export(format: ExportFormat, records: &[SessionRecord]) -> serde_json::Result<String> {
+    match format {
+        ExportFormat::Csv => Ok(to_csv(records)),
+        ExportFormat::Json => to_json(records),
+    }
+}
+
+/// None of the columns can contain commas or quotes, so no field needs escaping.
+fn to_csv(records: &[SessionRecord]) -> String {
+    let mut csv = "date,clock_in,clock_out,total_seconds,log_count\n".to_string();
+
+    for row in records.iter().map(Row::from_record) {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            row.date, row.clock_in, row.clock_out, row.total_seconds, row.log_count
+        ));
+    }
+
+    csv
+}
+
+fn to_json(records: &[SessionRecord]) -> serde_json::Result<String> {
+    let rows = records.iter().map(Row::from_record).collect::<Vec<Row>>();
+
+    Ok(serde_json::to_string_pretty(&rows)? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Local, TimeZone};
+    use serde_json::Value;
+
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 8, hour, minute, 0).unwrap()
+    }
+
+    fn sessions() -> Vec<SessionRecord> {
+        vec![
+            SessionRecord {
+                clocked_in_at: at(9, 0),
+                clocked_out_at: at(12, 30),
+                seconds: 12_600,
+                project: Some("Website".to_string()),
+                logs: vec!["#design header".to_string(), "footer".to_string()],
+                note: None,
+            },
+            SessionRecord {
+                clocked_in_at: at(13, 15),
+                clocked_out_at: at(13, 45),
+                seconds: 1_800,
+                project: None,
+                logs: vec![],
+                note: Some("Automatically clocked out".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn csv_has_a_header_and_one_row_per_session() {
+        let csv = to_csv(&sessions());
+        let lines = csv.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "date,clock_in,clock_out,total_seconds,log_count");
+        assert_eq!(
+            lines[1],
+            format!(
+                "2024-03-08,{},{},12600,2",
+                at(9, 0).to_rfc3339(),
+                at(12, 30).to_rfc3339()
+            )
+        );
+        assert_eq!(
+            lines[2],
+            format!(
+                "2024-03-08,{},{},1800,0",
+                at(13, 15).to_rfc3339(),
+                at(13, 45).to_rfc3339()
+            )
+        );
+    }
+
+    #[test]
+    fn csv_of_no_sessions_is_just_the_header() {
+        assert_eq!(
+            to_csv(&[]),
+            "date,clock_in,clock_out,total_seconds,log_count\n"
+        );
+    }
+
+    #[test]
+    fn json_round_trips_the_columns() {
+        let json: Value = serde_json::from_str(&to_json(&sessions()).unwrap()).unwrap();
+        let rows = json.as_array().unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["date"], "2024-03-08");
+        assert_eq!(rows[0]["clock_in"], at(9, 0).to_rfc3339());
+        assert_eq!(rows[0]["clock_out"], at(12, 30).to_rfc3339());
+        assert_eq!(rows[0]["total_seconds"], 12_600);
+        assert_eq!(rows[0]["log_count"], 2);
+        assert_eq!(rows[1]["total_seconds"], 1_800);
+        assert_eq!(rows[1]["log_count"], 0);
+    }
+}
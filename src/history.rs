@@ -0,0 +1,294 @@
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Days, Local, NaiveDate};
+use flate2::{Compression, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{self, StorageError};
+
+/// One completed work session, as appended to the history file on clock-out.
+#[derive(Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub clocked_in_at: DateTime<Local>,
+    pub clocked_out_at: DateTime<Local>,
+    pub seconds: u64,
+    #[serde(default)]
+    pub project: Option<String>,
+    pub logs: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Default)]
+pub struct DaySummary {
+    pub seconds: u64,
+    pub sessions: usize,
+    pub logs: usize,
+}
+
+/// Seconds worked on each day of one Monday-to-Sunday week.
+pub struct WeekSummary {
+    pub monday: NaiveDate,
+    pub days: [u64; 7],
+}
+
+impl WeekSummary {
+    pub fn total(&self) -> u64 {
+        self.days.iter().sum()
+    }
+}
+
+/// Completed sessions stored as JSON lines, one record per line.
+pub struct History {
+    path: PathBuf,
+    /// Where pruned records go, e.g. `history.archive.jsonl.gz` next to `history.jsonl`.
+    archive_path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> Self {
+        History {
+            archive_path: path.with_extension("archive.jsonl.gz"),
+            path,
+        }
+    }
+
+    pub fn append(&self, record: &SessionRecord) -> Result<(), StorageError> {
+        let mut line = serde_json::to_string(record)
+            .map_err(|error| StorageError::parse(&self.path, error))?;
+        line.push('\n');
+
+        storage::append(&self.path, &line)
+    }
+
+    /// Reads every record back, skipping lines that fail to parse. A missing file is empty.
+    pub fn load(&self) -> Result<Vec<SessionRecord>, StorageError> {
+        let Some(contents) = storage::read(&self.path)? else {
+            return Ok(vec![]);
+        };
+
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Moves the records clocked in before `before` out of the history file and into the
+    /// gzipped archive, returning how many were moved. Each prune appends its own gzip member,
+    /// so `zcat` reads the archive back as one list of JSON lines. Lines that fail to parse are
+    /// left in place.
+    pub fn prune(&self, before: NaiveDate) -> Result<usize, StorageError> {
+        let Some(contents) = storage::read(&self.path)? else {
+            return Ok(0);
+        };
+
+        let (pruned, kept): (Vec<&str>, Vec<&str>) = contents.lines().partition(|line| {
+            serde_json::from_str::<SessionRecord>(line)
+                .is_ok_and(|record| record.clocked_in_at.date_naive() < before)
+        });
+
+        if pruned.is_empty() {
+            return Ok(0);
+        }
+
+        let archive =
+            compress_lines(&pruned).map_err(|error| StorageError::io(&self.archive_path, error))?;
+
+        // The archive is written first, so a failure can't lose the pruned records.
+        storage::append_bytes(&self.archive_path, &archive)?;
+        storage::write(
+            &self.path,
+            &kept
+                .iter()
+                .map(|line| format!("{}\n", line))
+                .collect::<String>(),
+        )?;
+
+        Ok(pruned.len())
+    }
+
+    /// Totals the sessions clocked in on `date`.
+    pub fn summarize_day(&self, date: NaiveDate) -> Result<DaySummary, StorageError> {
+        Ok(self
+            .load()?
+            .iter()
+            .filter(|record| record.clocked_in_at.date_naive() == date)
+            .fold(DaySummary::default(), |summary, record| DaySummary {
+                seconds: summary.seconds + record.seconds,
+                sessions: summary.sessions + 1,
+                logs: summary.logs + record.logs.len(),
+            }))
+    }
+
+    pub fn summarize_week(&self, monday: NaiveDate) -> Result<WeekSummary, StorageError> {
+        Ok(week_totals(&self.load()?, monday))
+    }
+
+    pub fn streak(&self, today: NaiveDate) -> Result<u32, StorageError> {
+        Ok(streak(&self.load()?, today))
+    }
+}
+
+fn compress_lines(lines: &[&str]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+
+    for line in lines {
+        writeln!(encoder, "{}", line)?;
+    }
+
+    encoder.finish()
+}
+
+/// Buckets `records` by clock-in date into the week starting on `monday`.
+pub fn week_totals(records: &[SessionRecord], monday: NaiveDate) -> WeekSummary {
+    let mut days = [0; 7];
+
+    for record in records {
+        let day = (record.clocked_in_at.date_naive() - monday).num_days();
+
+        if let Ok(day @ 0..7) = usize::try_from(day) {
+            days[day] += record.seconds;
+        }
+    }
+
+    WeekSummary { monday, days }
+}
+
+/// Consecutive days worked up to `today`. A day with no sessions yet doesn't break the streak
+/// until it's over, so counting starts from yesterday when today is still empty.
+pub fn streak(records: &[SessionRecord], today: NaiveDate) -> u32 {
+    let worked = records
+        .iter()
+        .map(|record| record.clocked_in_at.date_naive())
+        .collect::<BTreeSet<NaiveDate>>();
+
+    let mut day = if worked.contains(&today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+
+    while worked.contains(&day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+
+    streak
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Read, process};
+
+    use chrono::TimeZone;
+    use flate2::read::MultiGzDecoder;
+
+    use super::*;
+
+    fn session(day: u32, hour: u32, seconds: u64) -> SessionRecord {
+        let clocked_in_at = Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap();
+
+        SessionRecord {
+            clocked_in_at,
+            clocked_out_at: clocked_in_at,
+            seconds,
+            project: None,
+            logs: vec![],
+            note: None,
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn week_totals_bucket_sessions_by_day() {
+        // 2024-03-04 is a Monday.
+        let records = [
+            session(3, 9, 100),
+            session(4, 9, 3_600),
+            session(4, 14, 1_800),
+            session(6, 9, 60),
+            session(10, 23, 7),
+            session(11, 9, 500),
+        ];
+        let week = week_totals(&records, date(4));
+
+        assert_eq!(week.days, [5_400, 0, 60, 0, 0, 0, 7]);
+        assert_eq!(week.total(), 5_467);
+    }
+
+    #[test]
+    fn week_totals_of_no_sessions_are_zero() {
+        assert_eq!(week_totals(&[], date(4)).days, [0; 7]);
+    }
+
+    #[test]
+    fn streak_counts_consecutive_days() {
+        let records = [
+            session(1, 9, 60),
+            session(3, 9, 60),
+            session(4, 9, 60),
+            session(4, 15, 60),
+            session(5, 9, 60),
+        ];
+
+        assert_eq!(streak(&records, date(5)), 3);
+        assert_eq!(streak(&records, date(6)), 3);
+        assert_eq!(streak(&records, date(7)), 0);
+        assert_eq!(streak(&records, date(2)), 1);
+        assert_eq!(streak(&[], date(5)), 0);
+    }
+
+    #[test]
+    fn prune_archives_records_before_the_cutoff() {
+        let dir = env::temp_dir().join(format!("workwatch-prune-{}", process::id()));
+        let history = History::new(dir.join("history.jsonl"));
+
+        for record in [session(1, 9, 60), session(4, 9, 120), session(5, 9, 180)] {
+            history.append(&record).unwrap();
+        }
+        storage::append(&history.path, "not a record\n").unwrap();
+
+        assert_eq!(history.prune(date(4)).unwrap(), 1);
+        assert_eq!(history.prune(date(4)).unwrap(), 0);
+        assert_eq!(history.prune(date(5)).unwrap(), 1);
+
+        let remaining = history.load().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].seconds, 180);
+        assert!(
+            storage::read(&history.path)
+                .unwrap()
+                .unwrap()
+                .ends_with("not a record\n")
+        );
+
+        let mut archived = String::new();
+        MultiGzDecoder::new(fs::File::open(&history.archive_path).unwrap())
+            .read_to_string(&mut archived)
+            .unwrap();
+        let archived = archived
+            .lines()
+            .map(|line| serde_json::from_str::<SessionRecord>(line).unwrap().seconds)
+            .collect::<Vec<u64>>();
+        assert_eq!(archived, [60, 120]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn prune_without_a_history_file_does_nothing() {
+        let dir = env::temp_dir().join(format!("workwatch-prune-missing-{}", process::id()));
+        let history = History::new(dir.join("history.jsonl"));
+
+        assert_eq!(history.prune(date(4)).unwrap(), 0);
+        assert!(!history.archive_path.exists());
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    ClockIn,
+    ClockOut,
+    AddLog,
+    InsertAbove,
+    InsertBelow,
+    EditLog,
+    AppendLog,
+    DeleteLog,
+    ViewLogs,
+    ViewTime,
+    Pause,
+    Pomodoro,
+    SessionNote,
+    AdjustTime,
+    CopySummary,
+    CopyTime,
+    Checkpoint,
+    Undo,
+    Filter,
+    ToggleDeltas,
+    CycleStamps,
+    Dedupe,
+    Presentation,
+    MoveUp,
+    MoveDown,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    Summary,
+    Week,
+    Settings,
+    Help,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 36] = [
+        Action::ClockIn,
+        Action::ClockOut,
+        Action::AddLog,
+        Action::InsertAbove,
+        Action::InsertBelow,
+        Action::EditLog,
+        Action::AppendLog,
+        Action::DeleteLog,
+        Action::ViewLogs,
+        Action::ViewTime,
+        Action::Pause,
+        Action::Pomodoro,
+        Action::SessionNote,
+        Action::AdjustTime,
+        Action::CopySummary,
+        Action::CopyTime,
+        Action::Checkpoint,
+        Action::Undo,
+        Action::Filter,
+        Action::ToggleDeltas,
+        Action::CycleStamps,
+        Action::Dedupe,
+        Action::Presentation,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::First,
+        Action::Last,
+        Action::Summary,
+        Action::Week,
+        Action::Settings,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::ClockIn => "Clock In",
+            Action::ClockOut => "Clock Out",
+            Action::AddLog => "Add Log",
+            Action::InsertAbove => "Insert Log Above Selected",
+            Action::InsertBelow => "Insert Log Below Selected",
+            Action::EditLog => "Edit Selected Log",
+            Action::AppendLog => "Append to Selected Log",
+            Action::DeleteLog => "Delete Selected Log",
+            Action::ViewLogs => "View Logs",
+            Action::ViewTime => "Return to Working",
+            Action::Pause => "Pause / Resume",
+            Action::Pomodoro => "Toggle Pomodoro Mode",
+            Action::SessionNote => "Edit Session Note",
+            Action::AdjustTime => "Adjust Elapsed Time",
+            Action::CopySummary => "Copy Summary to Clipboard",
+            Action::CopyTime => "Copy Elapsed Time to Clipboard",
+            Action::Checkpoint => "Post Logs as a Checkpoint and Clear Them",
+            Action::Undo => "Undo Log Change",
+            Action::Filter => "Filter Logs",
+            Action::ToggleDeltas => "Toggle Time Since Previous Log",
+            Action::CycleStamps => "Cycle Elapsed / Clock Time / Relative Stamps",
+            Action::Dedupe => "Remove Consecutive Duplicate Logs",
+            Action::Presentation => "Hide / Show Logs for Screen Sharing",
+            Action::MoveUp => "Move Selected Log Up",
+            Action::MoveDown => "Move Selected Log Down",
+            Action::Up => "Select Previous",
+            Action::Down => "Select Next",
+            Action::PageUp => "Page Up",
+            Action::PageDown => "Page Down",
+            Action::First => "Jump to First Log",
+            Action::Last => "Jump to Last Log",
+            Action::Summary => "Today's Summary",
+            Action::Week => "Weekly Overview",
+            Action::Settings => "Settings",
+            Action::Help => "Show / Hide Help",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// A single key name or a list of them, e.g. `"c"` or `["up", "k"]`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum KeyNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyNames {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            KeyNames::One(name) => vec![name],
+            KeyNames::Many(names) => names,
+        }
+    }
+}
+
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = [
+            (Action::ClockIn, vec![KeyCode::Char('c')]),
+            (Action::ClockOut, vec![KeyCode::Char('c')]),
+            (Action::AddLog, vec![KeyCode::Char('a')]),
+            (Action::InsertAbove, vec![KeyCode::Char('i')]),
+            (Action::InsertBelow, vec![KeyCode::Char('o')]),
+            (Action::EditLog, vec![KeyCode::Char('e')]),
+            (Action::AppendLog, vec![KeyCode::Char('A')]),
+            (Action::DeleteLog, vec![KeyCode::Char('d')]),
+            (Action::ViewLogs, vec![KeyCode::Char('l')]),
+            (Action::ViewTime, vec![KeyCode::Char('t')]),
+            (Action::Pause, vec![KeyCode::Char('p')]),
+            (Action::Pomodoro, vec![KeyCode::Char('m')]),
+            (Action::SessionNote, vec![KeyCode::Char('n')]),
+            (Action::AdjustTime, vec![KeyCode::Char('+')]),
+            (Action::CopySummary, vec![KeyCode::Char('y')]),
+            (Action::CopyTime, vec![KeyCode::Char('Y')]),
+            (Action::Checkpoint, vec![KeyCode::Char('C')]),
+            (Action::Undo, vec![KeyCode::Char('u')]),
+            (Action::Filter, vec![KeyCode::Char('/')]),
+            (Action::ToggleDeltas, vec![KeyCode::Char('x')]),
+            (Action::CycleStamps, vec![KeyCode::Char('T')]),
+            (Action::Dedupe, vec![KeyCode::Char('D')]),
+            (Action::Presentation, vec![KeyCode::Char('h')]),
+            (Action::MoveUp, vec![KeyCode::Char('K')]),
+            (Action::MoveDown, vec![KeyCode::Char('J')]),
+            (Action::Up, vec![KeyCode::Up, KeyCode::Char('k')]),
+            (Action::Down, vec![KeyCode::Down, KeyCode::Char('j')]),
+            (Action::PageUp, vec![KeyCode::PageUp]),
+            (Action::PageDown, vec![KeyCode::PageDown]),
+            (Action::First, vec![KeyCode::Home, KeyCode::Char('g')]),
+            (Action::Last, vec![KeyCode::End, KeyCode::Char('G')]),
+            (Action::Summary, vec![KeyCode::Char('s')]),
+            (Action::Week, vec![KeyCode::Char('w')]),
+            (Action::Settings, vec![KeyCode::Char('o')]),
+            (Action::Help, vec![KeyCode::Char('?')]),
+            (Action::Quit, vec![KeyCode::Char('q')]),
+        ];
+
+        Keymap {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Starts from the default bindings and replaces those of every action in `overrides`.
+    pub fn with_overrides(overrides: HashMap<Action, KeyNames>) -> Self {
+        let mut keymap = Keymap::default();
+
+        for (action, names) in overrides {
+            let keys = names
+                .into_vec()
+                .iter()
+                .filter_map(|name| {
+                    let key = parse_key(name);
+
+                    if key.is_none() {
+                        eprintln!(
+                            "WorkWatch Warning: unknown key {:?} in [keys]! Ignoring it.",
+                            name
+                        );
+                    }
+
+                    key
+                })
+                .collect();
+
+            keymap.bindings.insert(action, keys);
+        }
+
+        keymap
+    }
+
+    /// Returns the first of `actions` that `key` is bound to.
+    pub fn resolve(&self, key: KeyCode, actions: &[Action]) -> Option<Action> {
+        actions.iter().copied().find(|action| {
+            self.bindings
+                .get(action)
+                .is_some_and(|keys| keys.contains(&key))
+        })
+    }
+
+    /// The keys bound to `action`, formatted for the controls bar, e.g. `Up/K`.
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .map(|keys| {
+                keys.iter()
+                    .map(key_label)
+                    .collect::<Vec<String>>()
+                    .join("/")
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    match name.to_lowercase().as_str() {
+        "up" => Some(KeyCode::Up),
+        "down" => Some(KeyCode::Down),
+        "left" => Some(KeyCode::Left),
+        "right" => Some(KeyCode::Right),
+        "pageup" => Some(KeyCode::PageUp),
+        "pagedown" => Some(KeyCode::PageDown),
+        "home" => Some(KeyCode::Home),
+        "end" => Some(KeyCode::End),
+        "tab" => Some(KeyCode::Tab),
+        "backspace" => Some(KeyCode::Backspace),
+        "delete" => Some(KeyCode::Delete),
+        "insert" => Some(KeyCode::Insert),
+        "space" => Some(KeyCode::Char(' ')),
+        name => name
+            .strip_prefix('f')
+            .and_then(|number| number.parse().ok())
+            .map(KeyCode::F),
+    }
+}
+
+fn key_label(key: &KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) if c.is_uppercase() => format!("Shift+{}", c),
+        KeyCode::Char(c) => c.to_uppercase().to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        key => key.to_string(),
+    }
+}
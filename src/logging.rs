@@ -0,0 +1,59 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io,
+    path::Path,
+    sync::Mutex,
+};
+
+use tracing_subscriber::EnvFilter;
+
+/// Starts writing diagnostics to `workwatch.log` in `data_dir`, since the TUI owns the
+/// terminal. `RUST_LOG` takes precedence over `level`; with neither set nothing is logged.
+pub fn init(data_dir: &Path, level: Option<&str>) {
+    let filter = match (env::var("RUST_LOG"), level) {
+        (Ok(directives), _) => EnvFilter::try_new(&directives),
+        // Only this crate's events, so a `debug` level doesn't bury them under the HTTP stack's.
+        (Err(_), Some(level)) => EnvFilter::try_new(format!("workwatch={}", level)),
+        (Err(_), None) => return,
+    };
+
+    let filter = match filter {
+        Ok(filter) => filter,
+        Err(error) => {
+            eprintln!(
+                "WorkWatch Warning: invalid log level! Logging is off. ({})",
+                error
+            );
+            return;
+        }
+    };
+
+    let path = data_dir.join("workwatch.log");
+
+    let file = match open(&path) {
+        Ok(file) => file,
+        Err(error) => {
+            eprintln!(
+                "WorkWatch Warning: could not open {}! Logging is off. ({})",
+                path.display(),
+                error
+            );
+            return;
+        }
+    };
+
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .try_init();
+}
+
+fn open(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    OpenOptions::new().create(true).append(true).open(path)
+}
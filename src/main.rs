@@ -1,454 +1,5452 @@
-use std::{env, io, time::Duration};
+mod cli;
+mod config;
+mod export;
+mod history;
+mod keymap;
+mod logging;
+mod projects;
+mod storage;
+mod template;
+mod time_format;
 
-use chrono::Local;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    ops::ControlFlow,
+    path::PathBuf,
+    process,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use arboard::Clipboard;
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveTime};
+use clap::Parser;
+use crossterm::{
+    cursor::SetCursorStyle,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+};
 use dotenv::dotenv;
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Paragraph},
+    widgets::{Bar, BarChart, BarGroup, Block, BorderType, Clear, Gauge, Paragraph, Wrap},
+};
+use reqwest::{
+    Client, Response, StatusCode,
+    header::RETRY_AFTER,
+    multipart::{Form, Part},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::{
+    sync::mpsc::{self, UnboundedSender},
+    task::JoinHandle,
+};
+use tui_input::{Input, InputRequest, backend::crossterm::EventHandler};
+
+use crate::{
+    cli::{Cli, Command},
+    config::{
+        AttachExport, Config, DryRun, Mentions, MessageTemplates, Notifications, PomodoroConfig,
+        Theme, Timezone, WebhookFlavor, data_dir, save_setting, webhook_urls,
+    },
+    history::{DaySummary, History, SessionRecord, WeekSummary},
+    keymap::{Action, Keymap},
+    projects::ProjectTotals,
+    storage::StorageError,
+    time_format::{
+        TimeFormat, format_ago, format_compact, format_verbose, parse_adjustment, parse_short,
+    },
 };
-use reqwest::Client;
-use serde_json::json;
-use tui_input::{Input, backend::crossterm::EventHandler};
 
+#[derive(Clone, Copy, Serialize, Deserialize)]
 enum AppState {
     Menu,
+    DaySummary,
+    Week,
+    SelectProject,
+    Settings,
     Working,
     Logs,
+    /// Shown for a moment after clocking out, before returning to the Menu.
+    Summary,
+}
+
+/// A session's worked total together with the logged breaks already left out of it, so
+/// messages report both from one source.
+#[derive(Clone, Copy)]
+struct WorkedTime {
+    total: usize,
+    breaks: usize,
+}
+
+/// What the clock-out summary screen shows, captured as the session ends.
+struct SessionSummary {
+    shown_at: Instant,
+    worked: usize,
+    logs: usize,
+    /// Why the session ended on its own, e.g. at the scheduled clock-out time.
+    note: Option<String>,
+    project: Option<String>,
 }
 
 #[derive(PartialEq, Eq)]
 enum PromptState {
     Input,
     Edit,
+    Confirm,
+    Filter,
+    Setting,
+    SessionNote,
+    AdjustTime,
     NoPrompt,
 }
 
-pub struct WorkWatcherApp {
+/// The settings that can be changed at runtime from the Settings screen.
+#[derive(Clone, Copy)]
+enum Setting {
+    Username,
+    BotName,
+    WebhookUrl,
+}
+
+impl Setting {
+    const ALL: [Setting; 3] = [Setting::Username, Setting::BotName, Setting::WebhookUrl];
+
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Username => "Username",
+            Setting::BotName => "Bot Name",
+            Setting::WebhookUrl => "Webhook URL",
+        }
+    }
+
+    /// The key it's saved under in `workwatch.toml`.
+    fn key(self) -> &'static str {
+        match self {
+            Setting::Username => "username",
+            Setting::BotName => "bot_name",
+            Setting::WebhookUrl => "webhook_url",
+        }
+    }
+}
+
+/// How entries are stamped in the Logs view.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LogStamps {
+    /// Time since clocking in, e.g. `[01:02:05]`.
+    Elapsed,
+    /// Time of day the entry was added, e.g. `[10:02:05]`.
+    Clock,
+    /// Age of the entry, e.g. `[3m ago]`.
+    Relative,
+}
+
+impl LogStamps {
+    fn next(self) -> Self {
+        match self {
+            LogStamps::Elapsed => LogStamps::Clock,
+            LogStamps::Clock => LogStamps::Relative,
+            LogStamps::Relative => LogStamps::Elapsed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PomodoroPhase {
+    Work,
+    Break,
+}
+
+struct Pomodoro {
+    phase: PomodoroPhase,
+    /// Elapsed seconds at which the current phase began.
+    phase_started: usize,
+}
+
+#[derive(Clone, Copy)]
+enum WebhookStatus {
+    Idle,
+    Sending,
+    Sent(Instant),
+    Failed(Instant),
+}
+
+impl WebhookStatus {
+    /// How long a finished delivery stays visible before the indicator goes back to idle.
+    const LINGER: Duration = Duration::from_secs(5);
+
+    fn is_stale(&self) -> bool {
+        match self {
+            WebhookStatus::Sent(at) | WebhookStatus::Failed(at) => at.elapsed() >= Self::LINGER,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct LogEntry {
+    #[serde(default)]
+    tags: Vec<String>,
+    text: String,
+    at: Duration,
+    /// Set on `#break` entries that start with a length, e.g. `#break 45m lunch`. The length is
+    /// left in the text and subtracted from the worked total.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    break_length: Option<Duration>,
+    /// The wall-clock time the entry was added. Missing on entries saved by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Local>>,
+}
+
+impl LogEntry {
+    /// Builds an entry from prompt input, splitting any leading `#tags` off the text.
+    fn parse(input: &str, at: Duration) -> Self {
+        let mut tags = vec![];
+        let mut rest = input.trim_start();
+
+        while let Some(tagged) = rest.strip_prefix('#') {
+            let (tag, remainder) = tagged
+                .split_once(char::is_whitespace)
+                .unwrap_or((tagged, ""));
+
+            if tag.is_empty() {
+                break;
+            }
+
+            tags.push(tag.to_string());
+            rest = remainder.trim_start();
+        }
+
+        let break_length = tags
+            .iter()
+            .any(|tag| tag == "break")
+            .then(|| rest.split_whitespace().next())
+            .flatten()
+            .and_then(parse_short)
+            .map(|secs| Duration::from_secs(secs as u64));
+
+        LogEntry {
+            tags,
+            text: rest.trim_end().to_string(),
+            at,
+            break_length,
+            created_at: None,
+        }
+    }
+
+    /// The entry as it would be typed, with its tags written back in front of the text.
+    fn input(&self) -> String {
+        self.tags
+            .iter()
+            .map(|tag| format!("#{} ", tag))
+            .collect::<String>()
+            + &self.text
+    }
+
+    /// `[HH:MM:SS]`, relative to clock-in.
+    fn timestamp(&self) -> String {
+        let total = self.at.as_secs();
+
+        format!(
+            "[{:02}:{:02}:{:02}]",
+            total / 3_600,
+            (total / 60) % 60,
+            total % 60
+        )
+    }
+
+    /// `[HH:MM:SS]` in local time when the entry was added, falling back to `timestamp` for
+    /// entries without one.
+    fn clock_timestamp(&self) -> String {
+        match self.created_at {
+            Some(created_at) => created_at.format("[%H:%M:%S]").to_string(),
+            None => self.timestamp(),
+        }
+    }
+
+    /// Formats the entry as `[HH:MM:SS] #tags text`, or `[HH:MM:SS, HH:MM:SS] #tags text` with
+    /// the wall-clock time in `timezone` after the elapsed time when it's known.
+    fn stamped(&self, timezone: Timezone) -> String {
+        let stamp = match self.created_at {
+            Some(created_at) => format!(
+                "{}, {}]",
+                self.timestamp().trim_end_matches(']'),
+                timezone.convert(created_at).format("%H:%M:%S")
+            ),
+            None => self.timestamp(),
+        };
+
+        format!("{} {}", stamp, self.input().replace('\n', "\n  "))
+    }
+}
+
+/// A reversible change to the log list, recorded for undo.
+enum LogAction {
+    Add {
+        index: usize,
+    },
+    Edit {
+        index: usize,
+        previous: LogEntry,
+    },
+    Delete {
+        index: usize,
+        entry: LogEntry,
+    },
+    Swap {
+        first: usize,
+        second: usize,
+    },
+    /// Entries removed as duplicates, with the indices they had, in ascending order.
+    Dedupe {
+        removed: Vec<(usize, LogEntry)>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct SessionData {
     state: AppState,
     time: usize,
-    logs: Vec<String>,
+    #[serde(default)]
+    paused: bool,
+    #[serde(default)]
+    clocked_in_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    session_note: Option<String>,
+    logs: Vec<LogEntry>,
+    #[serde(default)]
+    selected_log: Option<usize>,
+    #[serde(default)]
+    clock_in_pending: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    checkpointed_logs: Vec<LogEntry>,
+    /// Set when the session was clocked in from the command line, so the time until the TUI or
+    /// `clockout` picks it up still counts.
+    #[serde(default)]
+    detached_since: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    day_splits: Vec<DaySplit>,
+    #[serde(default)]
+    time_adjustment: i64,
+}
+
+/// Where a running session crossed into a new day, so its time can be split between the two.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct DaySplit {
+    at: DateTime<Local>,
+    /// Seconds worked in the session up to `at`.
+    seconds: u64,
+}
+
+/// Restores the terminal when dropped, covering `?` early returns out of `run()` as well as
+/// unwinding panics. `ratatui::init()` additionally hooks panics so the terminal is restored
+/// before the panic message is printed.
+/// Lines held back from stderr are written out once the terminal is back to normal.
+struct TerminalGuard(HeldStderr);
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), SetCursorStyle::DefaultUserShape);
+        ratatui::restore();
+        self.0.release();
+    }
+}
+
+/// Dry-run lines bound for stderr. While the TUI owns the terminal they would draw over the
+/// frame, so they're held until it's restored.
+#[derive(Clone, Default)]
+struct HeldStderr(Arc<Mutex<Option<Vec<String>>>>);
+
+impl HeldStderr {
+    fn hold(&self) {
+        *self.0.lock().unwrap() = Some(vec![]);
+    }
+
+    fn write(&self, line: &str) -> io::Result<()> {
+        match self.0.lock().unwrap().as_mut() {
+            Some(held) => {
+                held.push(line.to_string());
+                Ok(())
+            }
+            None => io::stderr().write_all(line.as_bytes()),
+        }
+    }
+
+    fn release(&self) {
+        let held = self.0.lock().unwrap().take();
+
+        for line in held.into_iter().flatten() {
+            let _ = io::stderr().write_all(line.as_bytes());
+        }
+    }
+}
+
+const MAX_UNDO_HISTORY: usize = 50;
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+const STATUS_INTERVAL: Duration = Duration::from_secs(5);
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+/// Below this size the layout can't fit even the main view's borders.
+const MIN_HEIGHT: u16 = 3;
+const MIN_WIDTH: u16 = 20;
+const POMODORO_FLASH: Duration = Duration::from_secs(6);
+/// The actions available in the Logs view, which take precedence over typing a log number.
+const LOGS_ACTIONS: [Action; 24] = [
+    Action::ViewTime,
+    Action::AddLog,
+    Action::InsertAbove,
+    Action::InsertBelow,
+    Action::EditLog,
+    Action::AppendLog,
+    Action::Filter,
+    Action::ToggleDeltas,
+    Action::CycleStamps,
+    Action::Dedupe,
+    Action::DeleteLog,
+    Action::Undo,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::ClockOut,
+    Action::CopySummary,
+    Action::CopyTime,
+    Action::Checkpoint,
+    Action::Up,
+    Action::Down,
+    Action::PageUp,
+    Action::PageDown,
+    Action::First,
+    Action::Last,
+];
+const GOAL_FLASH: Duration = Duration::from_secs(6);
+const FOCUS_LOCK_OVERRIDE: Duration = Duration::from_secs(5);
+
+pub struct WorkWatcherApp {
+    state: AppState,
+    /// Seconds accumulated before the running segment, e.g. restored from a saved session.
+    base_seconds: usize,
+    started_at: Option<Instant>,
+    autosaved_at: Instant,
+    paused: bool,
+    clocked_in_at: Option<DateTime<Local>>,
+    project: Option<String>,
+    /// What the session set out to do, kept apart from the running task logs.
+    session_note: Option<String>,
+    pomodoro: Option<Pomodoro>,
+    pomodoro_alert: Option<Instant>,
+    logs: Vec<LogEntry>,
+    /// Logs posted and cleared by checkpoints during the running session, still counted in its
+    /// breaks, export and history.
+    checkpointed_logs: Vec<LogEntry>,
+    /// Each midnight the running session has passed, oldest first.
+    day_splits: Vec<DaySplit>,
     prompt_state: PromptState,
     prompt_input: Input,
+    /// Where the entry being typed will be inserted; `None` appends it.
+    insert_at: Option<usize>,
+    /// Position of the selected entry within the filtered view, not an index into `logs`.
     selected_log: Option<usize>,
-    client: Client,
+    log_filter: String,
+    /// Digits typed in the Logs view, jumping to that entry once a non-digit is pressed.
+    log_jump: String,
+    show_log_deltas: bool,
+    /// Hides log text and the session note on screen while screen sharing.
+    presenting: bool,
+    log_stamps: LogStamps,
+    log_scroll: usize,
+    log_page: usize,
+    undo_history: Vec<LogAction>,
+    webhook_status: Arc<Mutex<WebhookStatus>>,
+    webhook_queue: Option<UnboundedSender<WebhookJob>>,
+    webhook_worker: Option<JoinHandle<()>>,
     username: String,
+    banner: Option<String>,
     webhook_url: String,
+    webhook_flavor: Option<WebhookFlavor>,
+    mentions: Mentions,
+    attach_export: AttachExport,
+    /// Webhook URLs that replace `webhook_url` while clocked in on the named project.
+    project_webhooks: HashMap<String, String>,
     bot_name: String,
+    messages: MessageTemplates,
+    dry_run: Option<DryRun>,
+    held_stderr: HeldStderr,
+    theme: Theme,
+    session_path: PathBuf,
+    export_dir: PathBuf,
+    status_file: Option<PathBuf>,
+    /// When the status file was last written, and the state it was written with.
+    status_written: Option<(Instant, &'static str)>,
+    status_line_file: Option<PathBuf>,
+    /// The text last written to `status_line_file`, so unchanged lines aren't rewritten.
+    status_line: Option<String>,
+    pomodoro_config: PomodoroConfig,
+    projects: Vec<String>,
+    selected_project: usize,
+    selected_setting: usize,
+    project_totals: ProjectTotals,
+    history: History,
+    day_summary: DaySummary,
+    week_summary: WeekSummary,
+    streak: u32,
+    keymap: Keymap,
+    show_help: bool,
+    /// Set on startup when an unfinished session was restored, until it's resumed or archived.
+    confirm_resume: bool,
+    confirm_quit: bool,
+    /// The most recent failure to save, if any.
+    storage_error: Option<StorageError>,
+    refresh: Duration,
+    idle_refresh: Duration,
+    idle_timeout: Option<Duration>,
+    clock_out_at: Option<NaiveTime>,
+    recent_logs: usize,
+    last_input: Instant,
+    heartbeat_interval: Option<Duration>,
+    notify: Notifications,
+    min_session: Option<Duration>,
+    /// Set while the clock-in message waits for the session to reach `min_session`.
+    clock_in_pending: bool,
+    /// How many heartbeat intervals of the current session have already been announced.
+    heartbeats_sent: usize,
+    /// Elapsed seconds when a saved session was restored on launch, if one was.
+    restored_seconds: Option<usize>,
+    /// Net seconds added to or taken off the session by hand, noted when clocking out.
+    time_adjustment: i64,
+    daily_goal: Option<Duration>,
+    word_goal: Option<usize>,
+    time_format: Option<TimeFormat>,
+    timezone: Timezone,
+    sound: bool,
+    /// Draws a single status line instead of the bordered layout.
+    compact: bool,
+    focus_lock: Option<Duration>,
+    /// When a clock out was last refused by the focus lock.
+    clock_out_attempt: Option<Instant>,
+    /// Whether clocking out asks first; `confirm_clock_out` is set while it's asking.
+    clock_out_confirmation: bool,
+    confirm_clock_out: bool,
+    /// Kept open so copied text stays available on X11, where it lives only as long as its owner.
+    clipboard: Option<Clipboard>,
+    /// A short message shown in place of the controls, and when it was set.
+    notice: Option<(Instant, String)>,
+    /// Seconds from sessions already clocked out today, counted toward the daily goal.
+    worked_earlier_today: u64,
+    /// The day `worked_earlier_today` was read for, so it can be re-read after midnight.
+    worked_today_date: NaiveDate,
+    goal_reached: bool,
+    goal_alert: Option<Instant>,
+    /// Saved with the session so the clock keeps running after a `clockin` subcommand exits.
+    detached_since: Option<DateTime<Local>>,
+    /// How long the clock-out summary stays up; skipped entirely when unset.
+    summary_duration: Option<Duration>,
+    session_summary: Option<SessionSummary>,
+    /// The last session clocked out this run, printed on exit with `--json-summary`.
+    ended_session: Option<Value>,
 }
 
 impl WorkWatcherApp {
-    pub fn new(username: String, webhook_url: String) -> Self {
-        WorkWatcherApp {
+    pub fn new(config: Config) -> Self {
+        let client = Client::builder()
+            .timeout(config.webhook_timeout)
+            .build()
+            .unwrap_or_default();
+        let webhook_status = Arc::new(Mutex::new(WebhookStatus::Idle));
+        let held_stderr = HeldStderr::default();
+        let history_retention_days = config.history_retention_days;
+        let (webhook_queue, webhook_worker) = spawn_webhook_worker(
+            client,
+            Arc::clone(&webhook_status),
+            config.dry_run.clone(),
+            held_stderr.clone(),
+        );
+
+        let mut app = WorkWatcherApp {
             state: AppState::Menu,
-            time: 0,
+            base_seconds: 0,
+            started_at: None,
+            autosaved_at: Instant::now(),
+            paused: false,
+            clocked_in_at: None,
+            project: None,
+            session_note: None,
+            pomodoro: None,
+            pomodoro_alert: None,
             logs: vec![],
+            checkpointed_logs: vec![],
+            day_splits: vec![],
             prompt_state: PromptState::NoPrompt,
             prompt_input: Input::default(),
+            insert_at: None,
             selected_log: None,
-            client: Client::new(),
-            username,
-            webhook_url,
-            bot_name: "WorkWatch".to_string(),
+            log_filter: String::new(),
+            log_jump: String::new(),
+            show_log_deltas: false,
+            presenting: false,
+            log_stamps: LogStamps::Elapsed,
+            log_scroll: 0,
+            log_page: 1,
+            undo_history: vec![],
+            webhook_status,
+            webhook_queue: Some(webhook_queue),
+            webhook_worker: Some(webhook_worker),
+            username: config.username,
+            banner: config.banner,
+            webhook_url: config.webhook_url,
+            webhook_flavor: config.webhook_flavor,
+            mentions: config.mentions,
+            attach_export: config.attach_export,
+            project_webhooks: config.project_webhooks,
+            bot_name: config.bot_name,
+            messages: config.messages,
+            dry_run: config.dry_run,
+            held_stderr,
+            theme: config.theme,
+            session_path: config.data_dir.join("session.json"),
+            export_dir: config.export_dir,
+            status_file: config.status_file,
+            status_written: None,
+            status_line_file: config.status_line_file,
+            status_line: None,
+            pomodoro_config: config.pomodoro,
+            projects: config.projects,
+            selected_project: 0,
+            selected_setting: 0,
+            project_totals: ProjectTotals::load(config.data_dir.join("projects.json"))
+                .unwrap_or_else(|error| {
+                    warn_storage(&error);
+                    ProjectTotals::in_memory()
+                }),
+            history: History::new(config.data_dir.join("history.jsonl")),
+            day_summary: DaySummary::default(),
+            week_summary: WeekSummary {
+                monday: Local::now().date_naive(),
+                days: [0; 7],
+            },
+            streak: 0,
+            keymap: config.keymap,
+            show_help: false,
+            confirm_resume: false,
+            confirm_quit: false,
+            storage_error: None,
+            refresh: config.refresh,
+            idle_refresh: config.idle_refresh,
+            idle_timeout: config.idle_timeout,
+            clock_out_at: config.clock_out_at,
+            recent_logs: config.recent_logs,
+            last_input: Instant::now(),
+            heartbeat_interval: config.heartbeat_interval,
+            notify: config.notify,
+            min_session: config.min_session,
+            clock_in_pending: false,
+            heartbeats_sent: 0,
+            restored_seconds: None,
+            time_adjustment: 0,
+            daily_goal: config.daily_goal,
+            word_goal: config.word_goal,
+            time_format: config.time_format,
+            timezone: config.timezone,
+            sound: config.sound,
+            compact: config.compact,
+            focus_lock: config.focus_lock,
+            clock_out_confirmation: config.confirm_clock_out,
+            confirm_clock_out: false,
+            clock_out_attempt: None,
+            clipboard: None,
+            notice: None,
+            worked_earlier_today: 0,
+            worked_today_date: Local::now().date_naive(),
+            goal_reached: false,
+            goal_alert: None,
+            detached_since: None,
+            summary_duration: config.summary_duration,
+            session_summary: None,
+            ended_session: None,
+        };
+
+        if let Err(error) = app.load_session() {
+            warn_storage(&error);
         }
+        if let Some(days) = history_retention_days
+            && let Err(error) = app
+                .history
+                .prune(Local::now().date_naive() - Days::new(days))
+        {
+            warn_storage(&error);
+        }
+        app.heartbeats_sent = app.heartbeats_due();
+        app.refresh_worked_today();
+
+        app
     }
 
     pub fn run(&mut self) -> io::Result<()> {
+        self.held_stderr.hold();
         let mut terminal = ratatui::init();
+        let _guard = TerminalGuard(self.held_stderr.clone());
+        // Only shown while a prompt is open, where it marks the typing position.
+        let _ = execute!(io::stdout(), SetCursorStyle::BlinkingBar);
 
         loop {
+            if self.clocked_in() && self.autosaved_at.elapsed() >= AUTOSAVE_INTERVAL {
+                self.track_storage(self.save_session());
+                self.autosaved_at = Instant::now();
+            }
+
+            let state = self.status_state();
+
+            if self
+                .status_written
+                .is_none_or(|(written_at, written_state)| {
+                    written_at.elapsed() >= STATUS_INTERVAL || written_state != state
+                })
+            {
+                self.write_status(state);
+                self.status_written = Some((Instant::now(), state));
+            }
+
+            self.write_status_line(self.status_line_text());
+
+            if let (AppState::Summary, Some(summary), Some(duration)) =
+                (self.state, &self.session_summary, self.summary_duration)
+                && summary.shown_at.elapsed() >= duration
+            {
+                self.state = AppState::Menu;
+            }
+
+            if Local::now().date_naive() != self.worked_today_date {
+                self.start_new_day();
+            }
+
+            self.advance_pomodoro();
+            self.send_pending_clock_in();
+            self.send_due_heartbeat();
+            self.check_daily_goal();
+
+            if let Some(idle_timeout) = self.idle_timeout
+                && self.clocked_in()
+                && !self.confirm_resume
+                && self.last_input.elapsed() >= idle_timeout
+            {
+                self.ring_bell();
+                self.clock_out(Some(format!(
+                    "Automatically clocked out after {} of inactivity.",
+                    format_verbose(idle_timeout.as_secs() as usize)
+                )));
+            }
+
+            if let Some(clock_out_at) = self.clock_out_at
+                && self.clocked_in()
+                && !self.confirm_resume
+                && self
+                    .clocked_in_at
+                    .is_some_and(|at| scheduled_clock_out_due(at, Local::now(), clock_out_at))
+            {
+                self.ring_bell();
+                self.clock_out(Some(format!(
+                    "Automatically clocked out at the scheduled end of day ({}).",
+                    clock_out_at.format("%H:%M")
+                )));
+            }
+
+            {
+                let mut webhook_status = self.webhook_status.lock().unwrap();
+
+                if webhook_status.is_stale() {
+                    *webhook_status = WebhookStatus::Idle;
+                }
+            }
+
             terminal.draw(|frame| {
                 self.draw(frame);
             })?;
 
-            if event::poll(Duration::from_secs(1))? {
+            // The clock-out summary counts down to closing itself, so it redraws like a session.
+            let refresh = if self.clocked_in() || matches!(self.state, AppState::Summary) {
+                self.refresh
+            } else {
+                self.idle_refresh
+            };
+
+            if event::poll(refresh)? {
                 let key_event = event::read()?;
 
+                if let Event::Resize(..) = key_event {
+                    terminal.autoresize()?;
+                    terminal.clear()?;
+                    continue;
+                }
+
                 if let Event::Key(key) = key_event {
                     if key.kind == KeyEventKind::Release {
                         continue;
                     }
 
-                    match self.prompt_state {
-                        PromptState::Input => {
-                            self.prompt_input.handle_event(&key_event);
+                    if self.handle_key(key).is_break() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.write_status("offline");
+        self.write_status_line(String::new());
 
-                            match key.code {
-                                KeyCode::Enter => {
-                                    self.logs.push(self.prompt_input.value_and_reset());
+        Ok(())
+    }
 
-                                    if self.selected_log.is_none() {
-                                        self.selected_log = Some(0);
-                                    }
+    /// Applies one key press to the app state. Returns `Break` when the key quits the app.
+    fn handle_key(&mut self, key: KeyEvent) -> ControlFlow<()> {
+        let event = Event::Key(key);
 
-                                    self.prompt_state = PromptState::NoPrompt;
-                                }
-                                KeyCode::Esc => {
-                                    self.prompt_input.reset();
-                                    self.prompt_state = PromptState::NoPrompt;
-                                }
-                                _ => {}
-                            }
+        self.last_input = Instant::now();
+
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return self.interrupt();
+        }
 
-                            continue;
+        match self.prompt_state {
+            PromptState::Input => {
+                self.prompt_input.handle_event(&event);
+
+                match key.code {
+                    KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
+                        self.prompt_input.handle(InputRequest::InsertChar('\n'));
+                    }
+                    KeyCode::Enter => {
+                        let input = self.prompt_input.value_and_reset();
+
+                        if input.trim().is_empty() {
+                            self.insert_at = None;
+                        } else if let Some(index) = self.insert_at.take() {
+                            self.insert_log(index, &input);
+                        } else {
+                            self.logs.push(LogEntry {
+                                created_at: Some(Local::now()),
+                                ..LogEntry::parse(
+                                    &input,
+                                    Duration::from_secs(self.elapsed_secs() as u64),
+                                )
+                            });
+                            self.record(LogAction::Add {
+                                index: self.logs.len() - 1,
+                            });
+
+                            self.clamp_selection();
                         }
-                        PromptState::Edit => {
-                            self.prompt_input.handle_event(&key_event);
 
-                            match key.code {
-                                KeyCode::Enter => {
-                                    if let Some(index) = self.selected_log {
-                                        self.logs[index] = self.prompt_input.value_and_reset();
-                                    }
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    KeyCode::Esc => {
+                        self.prompt_input.reset();
+                        self.insert_at = None;
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    _ => {}
+                }
+
+                return ControlFlow::Continue(());
+            }
+            PromptState::Edit => {
+                self.prompt_input.handle_event(&event);
 
-                                    self.prompt_state = PromptState::NoPrompt;
-                                }
-                                KeyCode::Esc => {
-                                    self.prompt_input.reset();
-                                    self.prompt_state = PromptState::NoPrompt;
-                                }
-                                _ => {}
-                            }
+                match key.code {
+                    KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
+                        self.prompt_input.handle(InputRequest::InsertChar('\n'));
+                    }
+                    KeyCode::Enter => {
+                        let input = self.prompt_input.value_and_reset();
 
-                            continue;
+                        // Clearing a log's text is treated as deleting it.
+                        if input.trim().is_empty() {
+                            self.delete_selected_log();
+                        } else if let Some(index) = self.selected_index() {
+                            let edited = LogEntry {
+                                created_at: self.logs[index].created_at,
+                                ..LogEntry::parse(&input, self.logs[index].at)
+                            };
+                            let previous = std::mem::replace(&mut self.logs[index], edited);
+                            self.record(LogAction::Edit { index, previous });
+                            self.clamp_selection();
                         }
-                        PromptState::NoPrompt => {}
+
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    KeyCode::Esc => {
+                        self.prompt_input.reset();
+                        self.prompt_state = PromptState::NoPrompt;
                     }
+                    _ => {}
+                }
 
-                    match self.state {
-                        AppState::Menu => match key.code {
-                            KeyCode::Char('c') => {
-                                self.state = AppState::Working;
-                                self.send_clock_in_webhook();
-                                self.time = 0;
-                            }
-                            KeyCode::Char('q') => break,
-                            _ => {}
-                        },
-                        AppState::Working => match key.code {
-                            KeyCode::Char('c') => {
-                                self.state = AppState::Menu;
-                                self.send_clock_out_webhook();
-                                self.time = 0;
-                            }
-                            KeyCode::Char('a') => {
-                                self.prompt_state = PromptState::Input;
-                            }
-                            KeyCode::Char('l') => {
-                                self.state = AppState::Logs;
-                            }
-                            _ => {}
-                        },
-                        AppState::Logs => match key.code {
-                            KeyCode::Char('t') => {
-                                self.state = AppState::Working;
-                            }
-                            KeyCode::Char('a') => {
-                                self.prompt_state = PromptState::Input;
-                            }
-                            KeyCode::Char('e') => {
-                                if let Some(index) = self.selected_log {
-                                    self.prompt_input = self.logs[index].clone().into();
-                                    self.prompt_state = PromptState::Edit;
-                                }
-                            }
-                            KeyCode::Char('d') => {
-                                if let Some(index) = self.selected_log {
-                                    self.logs.remove(index);
-                                    if self.logs.is_empty() {
-                                        self.selected_log = None;
-                                    } else {
-                                        self.selected_log =
-                                            Some(index.saturating_sub(1).min(self.logs.len() - 1));
-                                    }
-                                }
-                            }
-                            KeyCode::Char('c') => {
-                                self.state = AppState::Menu;
-                                self.send_clock_out_webhook();
-                                self.time = 0;
-                            }
-                            KeyCode::Up | KeyCode::Char('k') => {
-                                if self.prompt_state != PromptState::Edit {
-                                    if let Some(index) = self.selected_log {
-                                        let len = self.logs.len();
-                                        self.selected_log = Some((index + len - 1) % len);
-                                    }
-                                }
-                            }
-                            KeyCode::Down | KeyCode::Char('j') => {
-                                if self.prompt_state != PromptState::Edit {
-                                    if let Some(index) = self.selected_log {
-                                        let len = self.logs.len();
-                                        self.selected_log = Some((index + 1) % len);
-                                    }
-                                }
-                            }
-                            _ => {}
-                        },
+                return ControlFlow::Continue(());
+            }
+            PromptState::Confirm => {
+                match key.code {
+                    KeyCode::Char('y') => {
+                        self.delete_selected_log();
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => {
+                        self.prompt_state = PromptState::NoPrompt;
                     }
+                    _ => {}
                 }
-            } else if let AppState::Working = self.state {
-                self.time = self.time.saturating_add(1);
+
+                return ControlFlow::Continue(());
             }
-        }
+            PromptState::Filter => {
+                self.prompt_input.handle_event(&event);
 
-        ratatui::restore();
+                match key.code {
+                    KeyCode::Enter => {
+                        self.prompt_input.reset();
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    KeyCode::Esc => {
+                        self.prompt_input.reset();
+                        self.set_log_filter(String::new());
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    _ => {
+                        self.set_log_filter(self.prompt_input.value().to_string());
+                    }
+                }
 
-        Ok(())
-    }
+                return ControlFlow::Continue(());
+            }
+            PromptState::Setting => {
+                self.prompt_input.handle_event(&event);
 
-    fn draw(&self, frame: &mut Frame) {
-        let area = frame.area();
+                match key.code {
+                    KeyCode::Enter => {
+                        let value = self.prompt_input.value_and_reset();
+                        self.apply_setting(value.trim());
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    KeyCode::Esc => {
+                        self.prompt_input.reset();
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    _ => {}
+                }
 
-        let title = match self.state {
-            AppState::Menu => "Menu",
-            AppState::Working => "Working",
-            AppState::Logs => "Logs",
-        };
+                return ControlFlow::Continue(());
+            }
+            PromptState::SessionNote => {
+                self.prompt_input.handle_event(&event);
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(match self.prompt_state {
-                PromptState::NoPrompt => vec![Constraint::Min(0), Constraint::Length(3)],
-                _ => vec![
-                    Constraint::Min(0),
-                    Constraint::Length(3),
-                    Constraint::Length(3),
-                ],
-            })
-            .split(area);
+                match key.code {
+                    KeyCode::Enter => {
+                        let value = self.prompt_input.value_and_reset();
+                        let value = value.trim();
 
-        frame.render_widget(
-            match self.state {
-                AppState::Menu => Paragraph::new(vec![Line::from(format!(
-                    "Welcome To WorkWatch, {}",
-                    self.username
-                ))]),
-                AppState::Working => Paragraph::new(vec![Line::from(format!(
-                    "Elapsed Time: {}",
-                    self.get_compact_time()
-                ))]),
-                AppState::Logs => Paragraph::new(if self.logs.is_empty() {
-                    vec![Line::from("No Logs Yet")]
-                } else {
-                    self.logs
-                        .iter()
-                        .enumerate()
-                        .map(|(index, log)| {
-                            if Some(index) == self.selected_log {
-                                Line::from(Span::styled(
-                                    log.as_str(),
-                                    Style::new()
-                                        .fg(Color::LightGreen)
-                                        .add_modifier(Modifier::BOLD),
-                                ))
-                            } else {
-                                Line::from(log.as_str())
-                            }
-                        })
-                        .collect::<Vec<Line>>()
-                }),
-            }
-            .block(
-                Block::bordered()
-                    .border_type(BorderType::Rounded)
-                    .title(title),
-            )
-            .alignment(Alignment::Center),
-            chunks[0],
-        );
+                        self.session_note = (!value.is_empty()).then(|| value.to_string());
+                        self.prompt_state = PromptState::NoPrompt;
+                        self.track_storage(self.save_session());
+                    }
+                    KeyCode::Esc => {
+                        self.prompt_input.reset();
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    _ => {}
+                }
 
-        match self.prompt_state {
-            PromptState::Input => {
-                frame.render_widget(
-                    Paragraph::new(self.prompt_input.to_string()).block(
-                        Block::bordered()
-                            .border_type(BorderType::Rounded)
-                            .title("Input"),
-                    ),
-                    chunks[1],
-                );
+                return ControlFlow::Continue(());
             }
-            PromptState::Edit => {
-                frame.render_widget(
-                    Paragraph::new(self.prompt_input.to_string()).block(
-                        Block::bordered()
-                            .border_type(BorderType::Rounded)
-                            .title("Edit"),
-                    ),
-                    chunks[1],
-                );
+            PromptState::AdjustTime => {
+                self.prompt_input.handle_event(&event);
+
+                match key.code {
+                    KeyCode::Enter => {
+                        let value = self.prompt_input.value_and_reset();
+
+                        match parse_adjustment(&value) {
+                            Some(delta) => self.adjust_time(delta),
+                            None => self.show_notice(
+                                "Adjust by a time like +10m, -5m or 15 (minutes)".to_string(),
+                            ),
+                        }
+
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    KeyCode::Esc => {
+                        self.prompt_input.reset();
+                        self.prompt_state = PromptState::NoPrompt;
+                    }
+                    _ => {}
+                }
+
+                return ControlFlow::Continue(());
             }
             PromptState::NoPrompt => {}
         }
 
-        frame.render_widget(
-            match self.state {
-                AppState::Menu => Paragraph::new(vec![Line::from(" C - Clock In | Q - Quit ")]),
-                AppState::Working => Paragraph::new(vec![Line::from(
-                    " L - View Logs | A - Add Log | C - Clock Out ",
-                )]),
-                AppState::Logs => Paragraph::new(vec![Line::from(
-                    " T - View Time | A - Add Log | E - Edit Log | D - Delete Log | C - Clock Out ",
-                )]),
-            }
-            .block(
-                Block::bordered()
-                    .border_type(BorderType::Rounded)
-                    .title("Controls"),
-            ),
-            chunks[match self.prompt_state {
-                PromptState::NoPrompt => 1,
-                _ => 2,
-            }],
-        );
-    }
+        if self.confirm_resume {
+            match key.code {
+                KeyCode::Char('r') | KeyCode::Enter => self.resume_session(),
+                KeyCode::Char('f') => self.discard_restored_session(),
+                _ => {}
+            }
 
-    fn send_clock_in_webhook(&self) {
-        if self.webhook_url.is_empty() {
-            return;
+            return ControlFlow::Continue(());
         }
 
-        let client = self.client.clone();
-        let webhook_url = self.webhook_url.clone();
-        let bot_name = self.bot_name.clone();
-        let username = self.username.clone();
+        if self.confirm_clock_out {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => self.clock_out(None),
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.confirm_clock_out = false;
+                }
+                _ => {}
+            }
 
-        tokio::spawn(async move {
-            let title = format!("{} has clocked in!", username);
-            let now = Local::now();
-            let date = now.format("%m/%d/%Y").to_string();
-            let time = now.format("%H:%M:%S (UTC%z)").to_string();
-            let description = format!("\nDate: {}\nTime: {}", date, time);
+            return ControlFlow::Continue(());
+        }
 
-            let embeds = [json!({
-                "title": title,
-                "description": description,
-                "color": 0x00ff88
-            })];
+        if self.confirm_quit {
+            match key.code {
+                KeyCode::Char('s') => {
+                    self.track_storage(self.save_session());
+                    return ControlFlow::Break(());
+                }
+                KeyCode::Char('d') => {
+                    let _ = fs::remove_file(&self.session_path);
+                    return ControlFlow::Break(());
+                }
+                KeyCode::Char('c') | KeyCode::Esc => {
+                    self.confirm_quit = false;
+                }
+                _ => {}
+            }
 
-            let payload = json!({
-                "username": bot_name,
-                "embeds": embeds
-            });
+            return ControlFlow::Continue(());
+        }
 
-            let _ = client.post(webhook_url).json(&payload).send().await;
-        });
-    }
+        if self.show_help {
+            if key.code == KeyCode::Esc || self.keymap.resolve(key.code, &[Action::Help]).is_some()
+            {
+                self.show_help = false;
+            }
 
-    fn send_clock_out_webhook(&self) {
-        if self.webhook_url.is_empty() {
-            return;
+            return ControlFlow::Continue(());
         }
 
-        let client = self.client.clone();
-        let webhook_url = self.webhook_url.clone();
-        let bot_name = self.bot_name.clone();
-        let username = self.username.clone();
-        let logs = self.logs.clone();
-        let total_time = self.get_verbose_time();
-
-        tokio::spawn(async move {
-            let title = format!("{} has clocked out!", username);
-            let now = Local::now();
-            let date = now.format("%m/%d/%Y").to_string();
-            let time = now.format("%H:%M:%S (UTC%z)").to_string();
-            let mut description = format!(
-                "\nDate: {}\nTime: {}\n\nTotal Logged Time: {}\n\n",
-                date, time, total_time
-            );
+        if self.keymap.resolve(key.code, &[Action::Help]).is_some() {
+            self.show_help = true;
+            return ControlFlow::Continue(());
+        }
 
-            if logs.is_empty() {
-                description.push_str("No logs to display.");
+        if self
+            .keymap
+            .resolve(key.code, &[Action::Presentation])
+            .is_some()
+        {
+            self.toggle_presentation();
+            return ControlFlow::Continue(());
+        }
+
+        if let AppState::Logs = self.state {
+            match key.code {
+                KeyCode::Char(digit)
+                    if digit.is_ascii_digit()
+                        && self.keymap.resolve(key.code, &LOGS_ACTIONS).is_none() =>
+                {
+                    self.log_jump.push(digit);
+                    return ControlFlow::Continue(());
+                }
+                KeyCode::Esc if !self.log_jump.is_empty() => {
+                    self.log_jump.clear();
+                    return ControlFlow::Continue(());
+                }
+                KeyCode::Enter if !self.log_jump.is_empty() => {
+                    self.jump_to_log();
+                    return ControlFlow::Continue(());
+                }
+                // Any other key completes the jump and is then handled as usual.
+                _ => self.jump_to_log(),
+            }
+        }
+
+        match self.state {
+            AppState::Menu => match self.keymap.resolve(
+                key.code,
+                &[
+                    Action::ClockIn,
+                    Action::Summary,
+                    Action::Week,
+                    Action::Settings,
+                    Action::Quit,
+                ],
+            ) {
+                Some(Action::ClockIn) if self.projects.is_empty() => self.clock_in(),
+                Some(Action::ClockIn) => {
+                    self.state = AppState::SelectProject;
+                }
+                Some(Action::Summary) => {
+                    self.day_summary = self
+                        .history
+                        .summarize_day(Local::now().date_naive())
+                        .unwrap_or_default();
+                    self.state = AppState::DaySummary;
+                }
+                Some(Action::Week) => {
+                    let today = Local::now().date_naive();
+                    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+
+                    self.load_week(monday);
+                    self.streak = self.history.streak(today).unwrap_or_default();
+                    self.state = AppState::Week;
+                }
+                Some(Action::Settings) => {
+                    self.state = AppState::Settings;
+                }
+                Some(Action::Quit) if self.has_unsaved_work() => {
+                    self.confirm_quit = true;
+                }
+                Some(Action::Quit) => {
+                    self.track_storage(self.save_session());
+                    return ControlFlow::Break(());
+                }
+                _ => {}
+            },
+            AppState::Summary => {
+                self.state = AppState::Menu;
+            }
+            AppState::DaySummary => {
+                if key.code == KeyCode::Esc
+                    || self.keymap.resolve(key.code, &[Action::Summary]).is_some()
+                {
+                    self.state = AppState::Menu;
+                }
+            }
+            AppState::Week => match key.code {
+                KeyCode::Left => {
+                    self.load_week(self.week_summary.monday - Days::new(7));
+                }
+                KeyCode::Right => {
+                    self.load_week(self.week_summary.monday + Days::new(7));
+                }
+                KeyCode::Esc => {
+                    self.state = AppState::Menu;
+                }
+                code if self.keymap.resolve(code, &[Action::Week]).is_some() => {
+                    self.state = AppState::Menu;
+                }
+                _ => {}
+            },
+            AppState::SelectProject => match key.code {
+                KeyCode::Enter => {
+                    self.project = Some(self.projects[self.selected_project].clone());
+                    self.clock_in();
+                }
+                KeyCode::Esc => {
+                    self.state = AppState::Menu;
+                }
+                code => match self.keymap.resolve(code, &[Action::Up, Action::Down]) {
+                    Some(Action::Up) => {
+                        let len = self.projects.len();
+                        self.selected_project = (self.selected_project + len - 1) % len;
+                    }
+                    Some(Action::Down) => {
+                        self.selected_project = (self.selected_project + 1) % self.projects.len();
+                    }
+                    _ => {}
+                },
+            },
+            AppState::Settings => match key.code {
+                KeyCode::Enter => self.edit_selected_setting(),
+                KeyCode::Esc => {
+                    self.state = AppState::Menu;
+                }
+                code => match self.keymap.resolve(
+                    code,
+                    &[Action::EditLog, Action::Settings, Action::Up, Action::Down],
+                ) {
+                    Some(Action::EditLog) => self.edit_selected_setting(),
+                    Some(Action::Settings) => {
+                        self.state = AppState::Menu;
+                    }
+                    Some(Action::Up) => {
+                        let len = Setting::ALL.len();
+                        self.selected_setting = (self.selected_setting + len - 1) % len;
+                    }
+                    Some(Action::Down) => {
+                        self.selected_setting = (self.selected_setting + 1) % Setting::ALL.len();
+                    }
+                    _ => {}
+                },
+            },
+            AppState::Working => match self.keymap.resolve(
+                key.code,
+                &[
+                    Action::ClockOut,
+                    Action::AddLog,
+                    Action::ViewLogs,
+                    Action::Pause,
+                    Action::Pomodoro,
+                    Action::SessionNote,
+                    Action::AdjustTime,
+                    Action::CopySummary,
+                    Action::CopyTime,
+                    Action::Checkpoint,
+                ],
+            ) {
+                Some(Action::ClockOut) => self.request_clock_out(),
+                Some(Action::AdjustTime) => {
+                    self.prompt_state = PromptState::AdjustTime;
+                }
+                Some(Action::AddLog) => {
+                    self.prompt_state = PromptState::Input;
+                }
+                Some(Action::SessionNote) if self.presenting => {
+                    self.show_notice("The note is hidden while presenting".to_string());
+                }
+                Some(Action::SessionNote) => {
+                    self.prompt_input = self.session_note.clone().unwrap_or_default().into();
+                    self.prompt_state = PromptState::SessionNote;
+                }
+                Some(Action::CopySummary) => self.copy_summary(),
+                Some(Action::CopyTime) => self.copy_time(),
+                Some(Action::Checkpoint) => self.checkpoint(),
+                Some(Action::ViewLogs) => {
+                    self.state = AppState::Logs;
+                }
+                Some(Action::Pause) => self.toggle_pause(),
+                Some(Action::Pomodoro) => self.toggle_pomodoro(),
+                _ => {}
+            },
+            AppState::Logs if key.code == KeyCode::Esc => {
+                self.set_log_filter(String::new());
+            }
+            AppState::Logs => match self.keymap.resolve(key.code, &LOGS_ACTIONS) {
+                Some(Action::ViewTime) => {
+                    self.state = AppState::Working;
+                }
+                Some(Action::AddLog) => {
+                    self.prompt_state = PromptState::Input;
+                }
+                Some(Action::InsertAbove) => {
+                    if let Some(index) = self.selected_index() {
+                        self.insert_at = Some(index);
+                        self.prompt_state = PromptState::Input;
+                    }
+                }
+                Some(Action::InsertBelow) => {
+                    if let Some(index) = self.selected_index() {
+                        self.insert_at = Some(index + 1);
+                        self.prompt_state = PromptState::Input;
+                    }
+                }
+                Some(Action::EditLog | Action::AppendLog) if self.presenting => {
+                    self.show_notice("Logs are hidden while presenting".to_string());
+                }
+                Some(Action::EditLog) => {
+                    if let Some(index) = self.selected_index() {
+                        self.prompt_input = self.logs[index].input().into();
+                        self.prompt_state = PromptState::Edit;
+                    }
+                }
+                Some(Action::AppendLog) => {
+                    if let Some(index) = self.selected_index() {
+                        let mut text = self.logs[index].input();
+
+                        // Continue after a space, unless the log already ends a line.
+                        if !text.ends_with(char::is_whitespace) {
+                            text.push(' ');
+                        }
+
+                        self.prompt_input = text.into();
+                        self.prompt_state = PromptState::Edit;
+                    }
+                }
+                Some(Action::Filter) => {
+                    self.prompt_input = self.log_filter.clone().into();
+                    self.prompt_state = PromptState::Filter;
+                }
+                Some(Action::ToggleDeltas) => {
+                    self.show_log_deltas = !self.show_log_deltas;
+                }
+                Some(Action::CycleStamps) => {
+                    self.log_stamps = self.log_stamps.next();
+                }
+                Some(Action::Dedupe) => self.dedupe_logs(),
+                Some(Action::DeleteLog) if self.selected_log.is_some() => {
+                    self.prompt_state = PromptState::Confirm;
+                }
+                Some(Action::Undo) => self.undo(),
+                Some(Action::MoveUp) => self.move_selected_log(-1),
+                Some(Action::MoveDown) => self.move_selected_log(1),
+                Some(Action::ClockOut) => self.request_clock_out(),
+                Some(Action::CopySummary) => self.copy_summary(),
+                Some(Action::CopyTime) => self.copy_time(),
+                Some(Action::Checkpoint) => self.checkpoint(),
+                Some(Action::Up) => self.step_selection(-1, true),
+                Some(Action::Down) => self.step_selection(1, true),
+                Some(Action::PageUp) => {
+                    self.step_selection(-(self.log_page as isize), false);
+                }
+                Some(Action::PageDown) => {
+                    self.step_selection(self.log_page as isize, false);
+                }
+                // A step the length of the list always lands on the end.
+                Some(Action::First) => {
+                    self.step_selection(-(self.visible_logs().len() as isize), false);
+                }
+                Some(Action::Last) => {
+                    self.step_selection(self.visible_logs().len() as isize, false);
+                }
+                _ => {}
+            },
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Remembers a failed write so it can be flagged on screen and reported on exit. The app
+    /// keeps running on its in-memory state either way.
+    fn track_storage(&mut self, result: Result<(), StorageError>) {
+        if let Err(error) = result {
+            tracing::error!(%error, "storage write failed");
+            self.storage_error = Some(error);
+        }
+    }
+
+    fn clocked_in(&self) -> bool {
+        matches!(self.state, AppState::Working | AppState::Logs)
+    }
+
+    /// Handles Ctrl+C, which raw mode delivers as a key rather than a signal. A running session
+    /// is clocked out as usual before saving and quitting, so nothing is left half-finished.
+    fn interrupt(&mut self) -> ControlFlow<()> {
+        tracing::info!("interrupted");
+
+        // A restored session is left for the resume prompt on the next launch.
+        if self.clocked_in() && !self.confirm_resume {
+            self.clock_out(None);
+        }
+
+        self.track_storage(self.save_session());
+        ControlFlow::Break(())
+    }
+
+    /// Whether quitting could lose something: a running session or logs still on the list.
+    fn has_unsaved_work(&self) -> bool {
+        self.clocked_in() || !self.logs.is_empty()
+    }
+
+    fn load_week(&mut self, monday: NaiveDate) {
+        self.week_summary = self.history.summarize_week(monday).unwrap_or(WeekSummary {
+            monday,
+            days: [0; 7],
+        });
+    }
+
+    fn setting_value(&self, setting: Setting) -> &str {
+        match setting {
+            Setting::Username => &self.username,
+            Setting::BotName => &self.bot_name,
+            Setting::WebhookUrl => &self.webhook_url,
+        }
+    }
+
+    fn edit_selected_setting(&mut self) {
+        let setting = Setting::ALL[self.selected_setting];
+        self.prompt_input = self.setting_value(setting).into();
+        self.prompt_state = PromptState::Setting;
+    }
+
+    /// Updates the selected setting and writes it to `workwatch.toml`. A blank username or bot
+    /// name is ignored, while a blank webhook URL turns messages off.
+    fn apply_setting(&mut self, value: &str) {
+        let setting = Setting::ALL[self.selected_setting];
+
+        match setting {
+            Setting::Username if !value.is_empty() => self.username = value.to_string(),
+            Setting::BotName if !value.is_empty() => self.bot_name = value.to_string(),
+            Setting::WebhookUrl => {
+                self.webhook_url = value.to_string();
+                self.webhook_flavor = None;
+            }
+            _ => return,
+        }
+
+        if let Err(error) = save_setting(setting.key(), value) {
+            tracing::error!(%error, "could not save setting");
+            self.show_notice(format!(
+                "Could not save {} to workwatch.toml ({})",
+                setting.label(),
+                error
+            ));
+        }
+    }
+
+    fn clock_in(&mut self) {
+        self.state = AppState::Working;
+        self.base_seconds = 0;
+        self.time_adjustment = 0;
+        self.heartbeats_sent = 0;
+        self.restored_seconds = None;
+        self.started_at = Some(Instant::now());
+        self.clocked_in_at = Some(Local::now());
+        self.clock_in_pending = true;
+        tracing::info!(project = ?self.project, "clocked in");
+        self.send_pending_clock_in();
+        self.refresh_worked_today();
+        self.track_storage(self.save_session());
+    }
+
+    /// Runs a `clockin` or `clockout` subcommand against the saved session without starting
+    /// the TUI.
+    pub fn run_command(&mut self, command: Command) -> Result<(), &'static str> {
+        match command {
+            Command::ClockIn => {
+                if self.clocked_in() {
+                    return Err("already clocked in");
+                }
+
+                self.detached_since = Some(Local::now());
+                self.clock_in();
+                println!("Clocked in as {}.", self.username);
+            }
+            Command::ClockOut { summary } => {
+                if !self.clocked_in() {
+                    return Err("not clocked in");
+                }
+
+                if let Some(summary) = summary.filter(|summary| !summary.trim().is_empty()) {
+                    self.logs.push(LogEntry {
+                        created_at: Some(Local::now()),
+                        ..LogEntry::parse(&summary, Duration::from_secs(self.elapsed_secs() as u64))
+                    });
+                }
+
+                let total = self.get_verbose_time();
+                self.detached_since = None;
+                self.clock_out(None);
+                println!("Clocked out after {}.", total);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Clocks in on launch for `--start-working`, going through the project picker when there
+    /// are projects to pick from. A restored session is left to the resume prompt instead.
+    fn start_working(&mut self) {
+        if self.clocked_in() || self.confirm_resume {
+            return;
+        }
+
+        if self.projects.is_empty() {
+            self.clock_in();
+        } else {
+            self.state = AppState::SelectProject;
+        }
+    }
+
+    /// Clocks out from a keypress, unless the focus lock is still running. A second press
+    /// within `FOCUS_LOCK_OVERRIDE` of the first overrides the lock.
+    fn request_clock_out(&mut self) {
+        let overriding = self
+            .clock_out_attempt
+            .take()
+            .is_some_and(|attempt| attempt.elapsed() < FOCUS_LOCK_OVERRIDE);
+
+        if self.focus_lock_remaining().is_some() && !overriding {
+            self.clock_out_attempt = Some(Instant::now());
+            return;
+        }
+
+        if self.clock_out_confirmation {
+            self.confirm_clock_out = true;
+            return;
+        }
+
+        self.clock_out(None);
+    }
+
+    /// Seconds left before the focus lock allows clocking out, if it's still running.
+    fn focus_lock_remaining(&self) -> Option<u64> {
+        self.focus_lock
+            .and_then(|lock| lock.as_secs().checked_sub(self.elapsed_secs() as u64))
+            .filter(|&remaining| remaining > 0)
+    }
+
+    /// Shown in place of the controls right after a clock out was refused by the focus lock.
+    fn focus_lock_notice(&self) -> Option<String> {
+        let remaining = self.focus_lock_remaining()?;
+
+        self.clock_out_attempt
+            .filter(|attempt| attempt.elapsed() < FOCUS_LOCK_OVERRIDE)
+            .map(|_| {
+                let minutes = remaining.div_ceil(60);
+
+                format!(
+                    " Locked for {} more {} | {} again - Clock Out Anyway ",
+                    minutes,
+                    if minutes == 1 { "minute" } else { "minutes" },
+                    self.keymap.label(Action::ClockOut)
+                )
+            })
+    }
+
+    /// Copies the running session's summary, worded like the clock-out message, to the system
+    /// clipboard.
+    fn copy_summary(&mut self) {
+        let summary = clock_out_description(
+            Local::now(),
+            self.timezone,
+            self.worked_time(),
+            self.verbose_format(),
+            self.session_note.as_deref(),
+            &self.logs,
+            None,
+        );
+        let summary = summary.trim_start().to_string();
+
+        self.copy_to_clipboard(summary, "Summary copied to the clipboard".to_string());
+    }
+
+    /// Copies the elapsed time as shown on screen, e.g. for a timesheet.
+    fn copy_time(&mut self) {
+        let time = self.get_compact_time();
+        let copied = format!("Copied {} to the clipboard", time);
+
+        self.copy_to_clipboard(time, copied);
+    }
+
+    /// Puts `text` on the clipboard, then shows `copied`, or why it couldn't be copied.
+    fn copy_to_clipboard(&mut self, text: String, copied: String) {
+        let result = match &mut self.clipboard {
+            Some(clipboard) => clipboard.set_text(text),
+            None => Clipboard::new().and_then(|mut clipboard| {
+                clipboard.set_text(text)?;
+                self.clipboard = Some(clipboard);
+                Ok(())
+            }),
+        };
+
+        self.show_notice(match result {
+            Ok(()) => copied,
+            Err(_) => "Could not access the clipboard".to_string(),
+        });
+    }
+
+    /// Posts the current logs as a partial summary and clears them, keeping the clock running.
+    /// The cleared logs still count toward the session's breaks, export and history.
+    fn checkpoint(&mut self) {
+        if self.logs.is_empty() {
+            self.show_notice("No logs to checkpoint".to_string());
+            return;
+        }
+
+        self.send_checkpoint_webhook();
+
+        let count = self.logs.len();
+        self.checkpointed_logs.append(&mut self.logs);
+        self.undo_history.clear();
+        self.selected_log = None;
+        self.set_log_filter(String::new());
+        self.track_storage(self.save_session());
+
+        self.show_notice(format!(
+            "Checkpoint: {} {} archived{}",
+            count,
+            if count == 1 { "log" } else { "logs" },
+            if self.webhooks_enabled() {
+                ""
             } else {
-                description.push_str("Logs:\n");
-                description.push_str(logs.join("\n").as_str());
-            };
+                " (not posted)"
+            }
+        ));
+    }
+
+    /// Hides or reveals log text on screen. Everything keeps being tracked, saved and posted
+    /// as usual while it's hidden.
+    fn toggle_presentation(&mut self) {
+        self.presenting = !self.presenting;
+
+        let label = self.keymap.label(Action::Presentation);
+        self.show_notice(if self.presenting {
+            format!("Logs hidden, {} shows them again", label)
+        } else {
+            "Logs shown".to_string()
+        });
+    }
+
+    fn show_notice(&mut self, notice: String) {
+        self.notice = Some((Instant::now(), notice));
+    }
+
+    /// The current notice, until `NOTICE_DURATION` has passed.
+    fn notice(&self) -> Option<String> {
+        self.notice
+            .as_ref()
+            .filter(|(shown_at, _)| shown_at.elapsed() < NOTICE_DURATION)
+            .map(|(_, notice)| format!(" {} ", notice))
+    }
+
+    /// Ends the session. `note` marks clock-outs the user didn't trigger themselves and is
+    /// carried into the webhook, the export and the history record.
+    fn clock_out(&mut self, note: Option<String>) {
+        // Freeze the clock first so the webhook, export and project totals all see one total.
+        self.base_seconds = self.elapsed_secs();
+        self.started_at = None;
+        self.confirm_clock_out = false;
+
+        let notes: Vec<String> = [note, self.adjustment_note(), self.word_goal_note()]
+            .into_iter()
+            .flatten()
+            .collect();
+        let note = (!notes.is_empty()).then(|| notes.join(" "));
+        tracing::info!(
+            worked_secs = self.worked_secs(),
+            logs = self.logs.len(),
+            note = ?note,
+            "clocked out"
+        );
 
-            let embeds = [json!({
-                "title": title,
-                "description": description,
-                "color": 0x00ff88
-            })];
+        self.state = AppState::Menu;
+        self.send_pending_clock_in();
 
-            let payload = json!({
-                "username": bot_name,
-                "embeds": embeds
+        if self.summary_duration.is_some() {
+            self.session_summary = Some(SessionSummary {
+                shown_at: Instant::now(),
+                worked: self.worked_secs(),
+                logs: self.logs.len(),
+                note: note.clone(),
+                project: self.project.clone(),
             });
+            self.state = AppState::Summary;
+        }
+
+        // The clock-in message was never sent, so the whole session stays quiet.
+        if !std::mem::take(&mut self.clock_in_pending) {
+            self.send_clock_out_webhook(note.as_deref());
+        }
+
+        self.end_session(note);
+    }
+
+    /// Records the frozen session in the export, history and project totals, then resets it,
+    /// clearing its logs so the next session starts with an empty list.
+    fn end_session(&mut self, note: Option<String>) {
+        let _ = self.export_session(note.as_deref());
+
+        let clocked_out_at = Local::now();
+        let logs: Vec<LogEntry> = self
+            .checkpointed_logs
+            .iter()
+            .chain(&self.logs)
+            .cloned()
+            .collect();
+        let session = SessionRecord {
+            clocked_in_at: self.clocked_in_at.unwrap_or(clocked_out_at),
+            clocked_out_at,
+            seconds: self.worked_secs() as u64,
+            project: self.project.clone(),
+            logs: vec![],
+            note,
+        };
+        self.ended_session = Some(json_summary(&session, &logs));
+        let records = split_by_day(session, &logs, &self.day_splits);
+
+        for record in &records {
+            let appended = self.history.append(record);
+            self.track_storage(appended);
+
+            if let Some(project) = &record.project {
+                let date = record.clocked_in_at.date_naive();
+                let recorded = self.project_totals.record(project, date, record.seconds);
+                self.track_storage(recorded);
+            }
+        }
+
+        self.base_seconds = 0;
+        self.restored_seconds = None;
+        self.time_adjustment = 0;
+        self.started_at = None;
+        self.paused = false;
+        self.clocked_in_at = None;
+        self.project = None;
+        self.session_note = None;
+        self.clock_in_pending = false;
+        self.checkpointed_logs.clear();
+        self.logs.clear();
+        self.undo_history.clear();
+        self.selected_log = None;
+        self.day_splits.clear();
+        self.pomodoro = None;
+        self.track_storage(self.save_session());
+        self.refresh_worked_today();
+    }
+
+    /// Continues the session restored on startup, restarting its clock.
+    fn resume_session(&mut self) {
+        self.confirm_resume = false;
+
+        if !self.paused {
+            self.started_at = Some(Instant::now());
+        }
+    }
+
+    /// Archives the session restored on startup to the history without posting a clock-out
+    /// message, and starts with an empty log list.
+    fn discard_restored_session(&mut self) {
+        self.confirm_resume = false;
+        self.state = AppState::Menu;
+        self.end_session(Some("Archived on startup instead of resumed.".to_string()));
+    }
+
+    /// Inserts a new entry at `index` and selects it. It borrows the timestamp of the entry it
+    /// follows (or, at the top, the one it precedes) so the list stays in chronological order.
+    fn insert_log(&mut self, index: usize, input: &str) {
+        let at = index
+            .checked_sub(1)
+            .or((index < self.logs.len()).then_some(index))
+            .map_or(Duration::ZERO, |neighbor| self.logs[neighbor].at);
+
+        self.logs.insert(
+            index,
+            LogEntry {
+                created_at: Some(Local::now()),
+                ..LogEntry::parse(input, at)
+            },
+        );
+        self.record(LogAction::Add { index });
+        self.select_log(index);
+    }
+
+    /// Selects the visible entry numbered by the digits in `log_jump`, counting from 1 and
+    /// clamped to the list.
+    fn jump_to_log(&mut self) {
+        let jump = std::mem::take(&mut self.log_jump);
+        let len = self.visible_logs().len();
+
+        if jump.is_empty() || len == 0 {
+            return;
+        }
+
+        let number = jump.parse::<usize>().unwrap_or(usize::MAX);
+        self.selected_log = Some(number.clamp(1, len) - 1);
+    }
 
-            let _ = client.post(webhook_url).json(&payload).send().await;
+    fn delete_selected_log(&mut self) {
+        if let Some(index) = self.selected_index() {
+            let entry = self.logs.remove(index);
+            self.record(LogAction::Delete { index, entry });
+            self.selected_log = self.selected_log.map(|position| position.saturating_sub(1));
+            self.clamp_selection();
+        }
+    }
+
+    /// Swaps the selected entry with its visible neighbor `offset` rows away, keeping it selected.
+    /// Moving the first entry up or the last entry down does nothing rather than wrapping.
+    fn move_selected_log(&mut self, offset: isize) {
+        let Some(position) = self.selected_log else {
+            return;
+        };
+
+        let visible = self.visible_logs();
+        let Some(target) = position
+            .checked_add_signed(offset)
+            .filter(|&target| target < visible.len())
+        else {
+            return;
+        };
+
+        self.logs.swap(visible[position], visible[target]);
+        self.record(LogAction::Swap {
+            first: visible[position],
+            second: visible[target],
         });
+        self.selected_log = Some(target);
     }
 
-    fn get_compact_time(&self) -> String {
-        let total = self.time;
-        let sec = total % 60;
-        let min = (total / 60) % 60;
-        let hr = (total / 3_600) % 24;
-        let days = total / 86_400;
-
-        if days > 0 {
-            format!("{}:{:02}:{:02}:{:02}", days, hr, min, sec)
-        } else if hr > 0 {
-            format!("{:02}:{:02}:{:02}", hr, min, sec)
-        } else if min > 0 {
-            format!("{:02}:{:02}", min, sec)
+    /// Indices into `logs` of the entries matching the active filter, in display order.
+    fn visible_logs(&self) -> Vec<usize> {
+        let query = self.log_filter.to_lowercase();
+
+        self.logs
+            .iter()
+            .enumerate()
+            .filter(|(_, log)| log.input().to_lowercase().contains(&query))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Maps the selected position in the filtered view back to its index in `logs`.
+    fn selected_index(&self) -> Option<usize> {
+        self.selected_log
+            .and_then(|position| self.visible_logs().get(position).copied())
+    }
+
+    /// Keeps the selection within the filtered view, selecting the first entry if none was.
+    fn clamp_selection(&mut self) {
+        let len = self.visible_logs().len();
+
+        self.selected_log = if len == 0 {
+            None
         } else {
-            format!("{:02}", sec)
+            Some(self.selected_log.unwrap_or(0).min(len - 1))
+        };
+    }
+
+    /// Moves the selection `step` positions through the filtered view. The selection is only
+    /// ever `Some` while the view has entries, and an empty view clears it instead of moving.
+    fn step_selection(&mut self, step: isize, wrap: bool) {
+        let len = self.visible_logs().len();
+
+        self.selected_log = self
+            .selected_log
+            .and_then(|position| step_position(position, len, step, wrap));
+    }
+
+    /// Selects the entry at `index` in `logs`, or the closest visible one if it is filtered out.
+    fn select_log(&mut self, index: usize) {
+        let visible = self.visible_logs();
+
+        self.selected_log = visible
+            .iter()
+            .position(|&visible_index| visible_index >= index)
+            .or(visible.len().checked_sub(1));
+    }
+
+    fn set_log_filter(&mut self, filter: String) {
+        self.log_filter = filter;
+        self.selected_log = None;
+        self.clamp_selection();
+    }
+
+    fn record(&mut self, action: LogAction) {
+        if self.undo_history.len() == MAX_UNDO_HISTORY {
+            self.undo_history.remove(0);
         }
+
+        self.undo_history.push(action);
     }
 
-    fn get_verbose_time(&self) -> String {
-        let total = self.time;
-        let sec = total % 60;
-        let min = (total / 60) % 60;
-        let hr = (total / 3_600) % 24;
-        let days = total / 86_400;
+    fn undo(&mut self) {
+        let Some(action) = self.undo_history.pop() else {
+            return;
+        };
 
-        match (days, hr, min) {
-            (d, _, _) if d > 0 => {
-                format!("{} Days, {} Hours, {} Minutes, {} Seconds", d, hr, min, sec)
+        let index = match action {
+            LogAction::Add { index } => {
+                self.logs.remove(index);
+                index.saturating_sub(1)
             }
-            (_, h, _) if h > 0 => {
-                format!("{} Hours, {} Minutes, {} Seconds", h, min, sec)
+            LogAction::Edit { index, previous } => {
+                self.logs[index] = previous;
+                index
             }
-            (_, _, m) if m > 0 => {
-                format!("{} Minutes, {} Seconds", m, sec)
+            LogAction::Delete { index, entry } => {
+                self.logs.insert(index, entry);
+                index
             }
-            _ => {
-                format!("{} Seconds", sec)
+            LogAction::Swap { first, second } => {
+                self.logs.swap(first, second);
+                first
             }
-        }
+            LogAction::Dedupe { removed } => {
+                let first = removed.first().map_or(0, |(index, _)| *index);
+
+                for (index, entry) in removed {
+                    self.logs.insert(index, entry);
+                }
+
+                first
+            }
+        };
+
+        self.select_log(index);
     }
-}
 
-#[tokio::main]
-async fn main() -> io::Result<()> {
-    dotenv().ok();
+    /// Collapses runs of identical consecutive entries into their first occurrence.
+    fn dedupe_logs(&mut self) {
+        let removed = remove_consecutive_duplicates(&mut self.logs);
 
-    let username = match env::var("WORKWATCH_USERNAME") {
-        Ok(username) => username,
-        Err(_) => {
-            eprintln!(
-                "WorkWatch Warning: WORKWATCH_USERNAME not found! Will default to Anonymous."
-            );
-            "Anonymous".to_string()
+        self.show_notice(match removed.len() {
+            0 => "No duplicate logs found".to_string(),
+            1 => "Removed 1 duplicate log".to_string(),
+            count => format!("Removed {} duplicate logs", count),
+        });
+
+        if !removed.is_empty() {
+            self.record(LogAction::Dedupe { removed });
+            self.clamp_selection();
         }
-    };
+    }
+
+    fn toggle_pause(&mut self) {
+        if self.paused {
+            self.started_at = Some(Instant::now());
+        } else {
+            self.base_seconds = self.elapsed_secs();
+            self.started_at = None;
+        }
+
+        self.paused = !self.paused;
+        tracing::info!(
+            paused = self.paused,
+            worked_secs = self.worked_secs(),
+            "pause toggled"
+        );
+        self.send_pause_webhook();
+        self.track_storage(self.save_session());
+    }
+
+    /// Moves the elapsed time by `delta` seconds, stopping at zero, for a clock in that came late
+    /// or a break that wasn't paused.
+    fn adjust_time(&mut self, delta: i64) {
+        let elapsed = self.elapsed_secs();
+        let delta = delta.max(-(elapsed as i64));
+
+        let Some(time_adjustment) = self.time_adjustment.checked_add(delta) else {
+            self.show_notice("That adjustment is too large".to_string());
+            return;
+        };
+        let target = elapsed.saturating_add_signed(delta as isize);
+        let segment = self.segment_secs();
 
-    let webhook_url = match env::var("WORKWATCH_WEBHOOK") {
-        Ok(webhook) => webhook,
-        Err(_) => {
-            eprintln!(
-                "WorkWatch Warning: WORKWATCH_WEBHOOK not found! Will not be able to post messages to discord!"
+        // The running segment is kept where it can be, so only the base offset moves.
+        if target >= segment {
+            self.base_seconds = target - segment;
+        } else {
+            self.base_seconds = 0;
+            self.started_at = Some(
+                Instant::now()
+                    .checked_sub(Duration::from_secs(target as u64))
+                    .unwrap_or_else(Instant::now),
             );
-            "".to_string()
         }
-    };
 
-    WorkWatcherApp::new(username, webhook_url).run()
+        self.time_adjustment = time_adjustment;
+        self.restored_seconds = self
+            .restored_seconds
+            .map(|restored| restored.saturating_add_signed(delta as isize));
+        self.track_storage(self.save_session());
+        self.show_notice(format!(
+            "Elapsed time adjusted by {}",
+            format_adjustment(delta)
+        ));
+    }
+
+    /// The line clocking out adds to the note when the time was adjusted by hand.
+    fn adjustment_note(&self) -> Option<String> {
+        (self.time_adjustment != 0).then(|| {
+            format!(
+                "Includes a {} manual adjustment.",
+                format_adjustment(self.time_adjustment)
+            )
+        })
+    }
+
+    /// Words written in the session's logs, leaving out their tags.
+    fn session_words(&self) -> usize {
+        self.checkpointed_logs
+            .iter()
+            .chain(&self.logs)
+            .map(|log| log.text.split_whitespace().count())
+            .sum()
+    }
+
+    /// Progress toward `word_goal`, e.g. `342 / 1000 words`.
+    fn word_progress(&self) -> Option<String> {
+        self.word_goal
+            .map(|goal| format!("{} / {} words", self.session_words(), goal))
+    }
+
+    /// The line clocking out adds to the note when a word goal is set.
+    fn word_goal_note(&self) -> Option<String> {
+        self.word_progress()
+            .map(|progress| format!("Wrote {}.", progress))
+    }
+
+    fn toggle_pomodoro(&mut self) {
+        self.pomodoro = match self.pomodoro {
+            Some(_) => None,
+            None => Some(Pomodoro {
+                phase: PomodoroPhase::Work,
+                phase_started: self.elapsed_secs(),
+            }),
+        };
+    }
+
+    fn pomodoro_length(&self, phase: PomodoroPhase) -> usize {
+        match phase {
+            PomodoroPhase::Work => self.pomodoro_config.work_length.as_secs() as usize,
+            PomodoroPhase::Break => self.pomodoro_config.break_length.as_secs() as usize,
+        }
+    }
+
+    /// Seconds left in the current Pomodoro phase, if Pomodoro mode is on.
+    fn pomodoro_remaining(&self) -> Option<usize> {
+        let pomodoro = self.pomodoro.as_ref()?;
+        let into_phase = self.elapsed_secs().saturating_sub(pomodoro.phase_started);
+
+        Some(
+            self.pomodoro_length(pomodoro.phase)
+                .saturating_sub(into_phase),
+        )
+    }
+
+    /// Switches to the next Pomodoro phase once the current one has run out.
+    fn advance_pomodoro(&mut self) {
+        if self.pomodoro_remaining() != Some(0) {
+            return;
+        }
+
+        let elapsed = self.elapsed_secs();
+        let Some(pomodoro) = self.pomodoro.as_mut() else {
+            return;
+        };
+
+        let finished = pomodoro.phase;
+        pomodoro.phase = match finished {
+            PomodoroPhase::Work => PomodoroPhase::Break,
+            PomodoroPhase::Break => PomodoroPhase::Work,
+        };
+        pomodoro.phase_started = elapsed;
+
+        self.pomodoro_alert = Some(Instant::now());
+        self.ring_bell();
+
+        if self.pomodoro_config.notify {
+            self.send_pomodoro_webhook(finished);
+        }
+    }
+
+    /// Sounds the terminal bell for timed events when `sound` is enabled.
+    fn ring_bell(&self) {
+        if self.sound {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+        }
+    }
+
+    /// Seconds worked today, including the part of the running session since midnight.
+    fn worked_today(&self) -> u64 {
+        let before_today = self.day_splits.last().map_or(0, |split| split.seconds);
+
+        self.worked_earlier_today + (self.worked_secs() as u64).saturating_sub(before_today)
+    }
+
+    /// Rolls the daily total and goal over after midnight. A running session is split here, so
+    /// the time before midnight stays with the day it was worked on.
+    fn start_new_day(&mut self) {
+        tracing::info!(clocked_in = self.clocked_in(), "new day");
+
+        if self.clocked_in() {
+            let split = DaySplit {
+                at: Local::now(),
+                seconds: self.worked_secs() as u64,
+            };
+            let previous = self.day_splits.last().map_or(0, |split| split.seconds);
+
+            self.day_splits.push(split);
+            self.send_new_day_webhook(split.seconds.saturating_sub(previous));
+            self.track_storage(self.save_session());
+            self.show_notice("New day: today's total starts over".to_string());
+        }
+
+        self.goal_alert = None;
+        self.refresh_worked_today();
+    }
+
+    /// Re-reads today's earlier sessions from history. A goal that was already met before this
+    /// session started isn't celebrated again.
+    fn refresh_worked_today(&mut self) {
+        self.worked_today_date = Local::now().date_naive();
+        self.worked_earlier_today = self
+            .history
+            .summarize_day(self.worked_today_date)
+            .map_or(0, |summary| summary.seconds);
+        self.goal_reached = self
+            .daily_goal
+            .is_some_and(|goal| self.worked_today() >= goal.as_secs());
+    }
+
+    fn check_daily_goal(&mut self) {
+        let Some(goal) = self.daily_goal else {
+            return;
+        };
+
+        if self.clocked_in() && !self.goal_reached && self.worked_today() >= goal.as_secs() {
+            self.goal_reached = true;
+            self.goal_alert = Some(Instant::now());
+            self.ring_bell();
+            self.send_goal_webhook(goal);
+        }
+    }
+
+    fn goal_flashing(&self) -> bool {
+        self.goal_alert.is_some_and(|alert| {
+            let since = alert.elapsed();
+            since < GOAL_FLASH && since.as_secs().is_multiple_of(2)
+        })
+    }
+
+    /// One bar per weekday of the viewed week, labeled with the time worked that day.
+    fn week_chart(&self, width: u16) -> BarChart<'_> {
+        let bars = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+            .iter()
+            .zip(self.week_summary.days)
+            .map(|(day, seconds)| {
+                Bar::default()
+                    .label(Line::from(*day))
+                    .value(seconds)
+                    .text_value(format!("{}h{:02}", seconds / 3_600, (seconds / 60) % 60))
+            })
+            .collect::<Vec<Bar>>();
+
+        BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width((width.saturating_sub(6) / 7).clamp(3, 9))
+            .bar_gap(1)
+            .bar_style(Style::new().fg(self.theme.accent))
+    }
+
+    /// A one-line bar of today's worked time against the daily goal.
+    fn goal_gauge(&self, goal: Duration) -> Gauge<'_> {
+        let worked = self.worked_today();
+        let color = if self.goal_flashing() {
+            Color::LightGreen
+        } else if self.goal_reached {
+            self.theme.selected
+        } else {
+            self.theme.accent
+        };
+
+        Gauge::default()
+            .gauge_style(Style::new().fg(color))
+            .ratio((worked as f64 / goal.as_secs() as f64).min(1.0))
+            .label(format!(
+                "Today {} / {}{}",
+                format_compact(worked as usize),
+                format_compact(goal.as_secs() as usize),
+                if self.goal_reached {
+                    " - Goal reached!"
+                } else {
+                    ""
+                }
+            ))
+    }
+
+    /// Whether the border should currently be lit up to signal a finished Pomodoro interval.
+    fn pomodoro_flashing(&self) -> bool {
+        self.pomodoro_alert.is_some_and(|alert| {
+            let since = alert.elapsed();
+            since < POMODORO_FLASH && since.as_secs().is_multiple_of(2)
+        })
+    }
+
+    fn save_session(&self) -> Result<(), StorageError> {
+        let session = SessionData {
+            state: self.state,
+            time: self.elapsed_secs(),
+            paused: self.paused,
+            clocked_in_at: self.clocked_in_at,
+            project: self.project.clone(),
+            session_note: self.session_note.clone(),
+            logs: self.logs.clone(),
+            // Saved as an index into the full list, since the filter isn't restored.
+            selected_log: self.selected_index(),
+            clock_in_pending: self.clock_in_pending,
+            checkpointed_logs: self.checkpointed_logs.clone(),
+            detached_since: self.detached_since,
+            day_splits: self.day_splits.clone(),
+            time_adjustment: self.time_adjustment,
+        };
+
+        let contents = serde_json::to_string_pretty(&session)
+            .map_err(|error| StorageError::parse(&self.session_path, error))?;
+
+        storage::write(&self.session_path, &contents)
+    }
+
+    /// Restores the saved session, if there is one.
+    fn load_session(&mut self) -> Result<(), StorageError> {
+        let Some(contents) = storage::read(&self.session_path)? else {
+            return Ok(());
+        };
+        let session: SessionData = serde_json::from_str(&contents)
+            .map_err(|error| StorageError::parse(&self.session_path, error))?;
+
+        self.logs = session.logs;
+        self.selected_log = match self.logs.len() {
+            0 => None,
+            len => Some(session.selected_log.unwrap_or(0).min(len - 1)),
+        };
+
+        // Sessions saved from the Logs view come back on the Working view.
+        if let AppState::Working | AppState::Logs = session.state {
+            self.state = AppState::Working;
+            self.base_seconds = session.time;
+            self.restored_seconds = Some(session.time);
+            self.paused = session.paused;
+            self.clocked_in_at = session.clocked_in_at;
+            self.project = session.project;
+            self.session_note = session.session_note;
+            self.clock_in_pending = session.clock_in_pending;
+            self.checkpointed_logs = session.checkpointed_logs;
+            self.day_splits = session.day_splits;
+            self.time_adjustment = session.time_adjustment;
+            // Sessions clocked in from the command line are expected to still be running.
+            self.confirm_resume = session.detached_since.is_none();
+
+            if let Some(detached_since) = session.detached_since
+                && !self.paused
+            {
+                let detached = Local::now().signed_duration_since(detached_since);
+                self.base_seconds += detached.num_seconds().max(0) as usize;
+            }
+
+            if !self.paused && !self.confirm_resume {
+                self.started_at = Some(Instant::now());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `working`, `paused` or `idle`, as written to the status file. It reads `offline` once the
+    /// app has quit.
+    fn status_state(&self) -> &'static str {
+        match (self.clocked_in(), self.paused) {
+            (true, false) => "working",
+            (true, true) => "paused",
+            (false, _) => "idle",
+        }
+    }
+
+    /// Writes the current state to `status_file`, if one is configured. Failures are ignored,
+    /// as the file is only a convenience for outside tools.
+    fn write_status(&self, state: &str) {
+        let Some(path) = &self.status_file else {
+            return;
+        };
+
+        let status = json!({
+            "state": state,
+            "username": self.username,
+            "project": self.project,
+            "clocked_in_at": self.clocked_in_at.map(|at| at.to_rfc3339()),
+            "worked_seconds": if self.clocked_in() { self.worked_secs() } else { 0 },
+            "updated_at": Local::now().to_rfc3339(),
+        });
+
+        let _ = storage::write(path, &format!("{:#}\n", status));
+    }
+
+    /// The one line shown by status bars, e.g. `WW 01:23:45 · 4 logs`.
+    fn status_line_text(&self) -> String {
+        let logs = format!(
+            "{} {}",
+            self.logs.len(),
+            if self.logs.len() == 1 { "log" } else { "logs" }
+        );
+
+        match (self.clocked_in(), self.paused) {
+            (true, false) => format!("WW {} · {}", self.get_compact_time(), logs),
+            (true, true) => format!("WW ⏸ {} · {}", self.get_compact_time(), logs),
+            (false, _) => "WW off".to_string(),
+        }
+    }
+
+    /// Writes `line` to `status_line_file`, if one is configured and the line has changed since
+    /// the last write. Like the status file, failures are ignored.
+    fn write_status_line(&mut self, line: String) {
+        let Some(path) = &self.status_line_file else {
+            return;
+        };
+
+        if self.status_line.as_ref() == Some(&line) {
+            return;
+        }
+
+        let _ = storage::write(path, &format!("{}\n", line));
+        self.status_line = Some(line);
+    }
+
+    /// Appends a Markdown record of the current session to `<export_dir>/<clock-in date>.md`.
+    fn export_session(&self, note: Option<&str>) -> io::Result<()> {
+        let clocked_in_at = self.clocked_in_at.unwrap_or_else(Local::now);
+        let path = self
+            .export_dir
+            .join(format!("{}.md", clocked_in_at.format("%Y-%m-%d")));
+
+        let mut contents = String::new();
+
+        if !path.exists() {
+            contents.push_str(&format!("# {}\n", clocked_in_at.format("%Y-%m-%d")));
+        }
+
+        contents.push_str(&self.session_markdown(note));
+
+        fs::create_dir_all(&self.export_dir)?;
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(contents.as_bytes())
+    }
+
+    /// The session's section of the Markdown export, ending now.
+    fn session_markdown(&self, note: Option<&str>) -> String {
+        let clocked_out_at = Local::now();
+        let clocked_in_at = self.clocked_in_at.unwrap_or(clocked_out_at);
+        let mut contents = String::new();
+
+        contents.push_str(&format!(
+            "\n## Session {} - {}\n\n",
+            clocked_in_at.format("%H:%M:%S"),
+            clocked_out_at.format("%H:%M:%S")
+        ));
+        if let Some(project) = &self.project {
+            contents.push_str(&format!("- Project: {}\n", project));
+        }
+        if let Some(session_note) = &self.session_note {
+            contents.push_str(&format!("- Session Note: {}\n", session_note));
+        }
+
+        contents.push_str(&format!(
+            "- Clocked In: {}\n",
+            clocked_in_at.format("%m/%d/%Y %H:%M:%S (UTC%z)")
+        ));
+        contents.push_str(&format!(
+            "- Clocked Out: {}\n",
+            clocked_out_at.format("%m/%d/%Y %H:%M:%S (UTC%z)")
+        ));
+        contents.push_str(&format!(
+            "- Total Logged Time: {}\n",
+            self.get_verbose_time()
+        ));
+
+        if let breaks @ 1.. = self.session_break_secs() {
+            contents.push_str(&format!(
+                "- Breaks: {}\n",
+                self.verbose_format().format(breaks)
+            ));
+        }
+
+        if let Some(note) = note {
+            contents.push_str(&format!("- Note: {}\n", note));
+        }
+
+        contents.push('\n');
+
+        if self.logs.is_empty() && self.checkpointed_logs.is_empty() {
+            contents.push_str("No logs to display.\n");
+        } else {
+            contents.push_str("### Logs\n\n");
+
+            for log in self.checkpointed_logs.iter().chain(&self.logs) {
+                contents.push_str(&format!("- {}\n", log.stamped(Timezone::Local)));
+            }
+        }
+
+        contents
+    }
+
+    /// The session export as a file for the clock-out message, if `attach_export` asks for one.
+    fn clock_out_attachment(&self, note: Option<&str>) -> Option<Attachment> {
+        let long = || {
+            let description = clock_out_description(
+                Local::now(),
+                self.timezone,
+                self.worked_time(),
+                self.verbose_format(),
+                self.session_note.as_deref(),
+                &self.logs,
+                note,
+            );
+
+            description.chars().count() > DISCORD_DESCRIPTION_LIMIT
+        };
+
+        let attach = match self.attach_export {
+            AttachExport::Never => false,
+            AttachExport::Long => long(),
+            AttachExport::Always => true,
+        };
+
+        attach.then(|| {
+            let clocked_in_at = self.clocked_in_at.unwrap_or_else(Local::now);
+
+            Attachment {
+                name: format!("session-{}.md", clocked_in_at.format("%Y-%m-%d-%H%M")),
+                contents: format!(
+                    "# {}\n{}",
+                    clocked_in_at.format("%Y-%m-%d"),
+                    self.session_markdown(note)
+                ),
+            }
+        })
+    }
+
+    /// Seconds since `log` was added, falling back to its offset into the running session for
+    /// entries without a creation time.
+    fn log_age(&self, log: &LogEntry) -> u64 {
+        match log.created_at {
+            Some(created_at) => Local::now()
+                .signed_duration_since(created_at)
+                .num_seconds()
+                .max(0) as u64,
+            None => (self.elapsed_secs() as u64).saturating_sub(log.at.as_secs()),
+        }
+    }
+
+    /// The lines of the entry at `index`, shown at `position` in the filtered Logs view.
+    fn log_lines(&self, position: usize, index: usize) -> Vec<Line<'_>> {
+        let log = &self.logs[index];
+        let mut text_lines = log.text.split('\n');
+        let stamp = match self.log_stamps {
+            LogStamps::Elapsed => log.timestamp(),
+            LogStamps::Clock => log.clock_timestamp(),
+            LogStamps::Relative => format!("[{}]", format_ago(self.log_age(log))),
+        };
+        let mut spans = vec![Span::raw(format!("{} ", stamp))];
+
+        spans.extend(
+            log.tags
+                .iter()
+                .map(|tag| Span::styled(format!("#{} ", tag), Style::new().fg(self.theme.tag))),
+        );
+        spans.push(Span::raw(text_lines.next().unwrap_or_default()));
+
+        // Measured against the previous entry in the full list, so the delta doesn't change when
+        // a filter hides the entries in between.
+        if self.show_log_deltas && index > 0 {
+            let since = log.at.saturating_sub(self.logs[index - 1].at);
+
+            spans.push(Span::styled(
+                format!(" (+{} since last)", format_delta(since.as_secs())),
+                Style::new().fg(Color::DarkGray),
+            ));
+        }
+
+        let style = if Some(position) == self.selected_log {
+            Style::new()
+                .fg(self.theme.selected)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::new()
+        };
+
+        std::iter::once(Line::from(spans))
+            .chain(text_lines.map(Line::from))
+            .map(|line| line.style(style))
+            .collect()
+    }
+
+    /// Adjusts the Logs scroll offset, in rows, so the selected entry stays within `rows`
+    /// visible lines of `width` columns. Long entries wrap, so an entry's height is the number
+    /// of rows its lines wrap to. Entries taller than the view are scrolled to their first line.
+    fn scroll_to_selected(&mut self, rows: usize, width: u16) {
+        self.log_page = rows.max(1);
+
+        let Some(selected) = self.selected_log else {
+            self.log_scroll = 0;
+            return;
+        };
+
+        let heights = self
+            .visible_logs()
+            .into_iter()
+            .enumerate()
+            .map(|(position, index)| {
+                Paragraph::new(self.log_lines(position, index))
+                    .wrap(Wrap { trim: false })
+                    .line_count(width)
+            })
+            .collect::<Vec<usize>>();
+        let top = heights[..selected].iter().sum::<usize>();
+        let bottom = top + heights[selected];
+
+        if top < self.log_scroll {
+            self.log_scroll = top;
+        } else if bottom > self.log_scroll + self.log_page {
+            self.log_scroll = (bottom - self.log_page).min(top);
+        }
+    }
+
+    /// Formats `entries` for the controls bar using the keys currently bound to each action.
+    fn controls(&self, entries: &[(Action, &str)]) -> String {
+        let controls = entries
+            .iter()
+            .map(|(action, description)| {
+                format!("{} - {}", self.keymap.label(*action), description)
+            })
+            .collect::<Vec<String>>()
+            .join(" | ");
+
+        format!(" {} ", controls)
+    }
+
+    /// Summarizes how much was logged, e.g. ` 4 logs, 212 total chars (avg 53) `.
+    fn log_stats(&self) -> String {
+        let count = self.logs.len();
+        let chars = self
+            .logs
+            .iter()
+            .map(|log| log.text.chars().count())
+            .sum::<usize>();
+
+        match count {
+            0 => " 0 logs ".to_string(),
+            1 => format!(" 1 log, {} total chars ", chars),
+            _ => format!(
+                " {} logs, {} total chars (avg {}) ",
+                count,
+                chars,
+                chars / count
+            ),
+        }
+    }
+
+    fn working_lines(&self) -> Vec<Line<'_>> {
+        let mut lines = vec![];
+
+        if let Some(note) = &self.session_note {
+            lines.push(Line::from(if self.presenting {
+                "Note: (hidden)".to_string()
+            } else {
+                format!("Note: {}", note)
+            }));
+        }
+
+        if let Some(project) = &self.project {
+            lines.push(Line::from(format!("Project: {}", project)));
+        }
+
+        lines.push(Line::from(format!(
+            "Elapsed Time: {}",
+            self.get_compact_time()
+        )));
+
+        if let breaks @ 1.. = self.session_break_secs() {
+            lines.push(Line::from(format!(
+                "Breaks: {} (not counted)",
+                format_compact(breaks)
+            )));
+        }
+
+        if let Some(progress) = self.word_progress() {
+            lines.push(Line::from(progress));
+        }
+
+        if self.time_adjustment != 0 {
+            lines.push(Line::from(format!(
+                "Adjusted: {}",
+                format_adjustment(self.time_adjustment)
+            )));
+        }
+
+        if let (Some(restored), Some(since_restore)) =
+            (self.restored_seconds, self.secs_since_restore())
+        {
+            lines.push(Line::from(format!(
+                "Since Relaunch: {} (restored {})",
+                format_compact(since_restore),
+                format_compact(restored)
+            )));
+        }
+
+        if self.worked_earlier_today > 0 {
+            lines.push(Line::from(format!(
+                "Today: {}",
+                format_compact(self.worked_today() as usize)
+            )));
+        }
+        lines.push(Line::from(format!(
+            "Now: {}",
+            Local::now().format("%H:%M:%S — %m/%d/%Y")
+        )));
+
+        if self.paused {
+            lines.push(Line::from(Span::styled(
+                "PAUSED",
+                Style::new()
+                    .fg(self.theme.paused)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+
+        let countdowns = self.countdowns();
+
+        if !countdowns.is_empty() {
+            lines.push(Line::styled(
+                countdowns.join(" | "),
+                Style::new().fg(Color::DarkGray),
+            ));
+        }
+
+        if let (Some(pomodoro), Some(remaining)) = (&self.pomodoro, self.pomodoro_remaining()) {
+            let phase = match pomodoro.phase {
+                PomodoroPhase::Work => "Work",
+                PomodoroPhase::Break => "Break",
+            };
+
+            lines.push(Line::from(format!(
+                "{} {:02}:{:02} remaining",
+                phase,
+                remaining / 60,
+                remaining % 60
+            )));
+        }
+
+        if self.recent_logs > 0 && !self.logs.is_empty() && !self.presenting {
+            lines.push(Line::from(""));
+            lines.extend(
+                self.logs[self.logs.len().saturating_sub(self.recent_logs)..]
+                    .iter()
+                    .map(|log| {
+                        Line::styled(
+                            format!("{} {}", log.timestamp(), log.input()),
+                            Style::new().fg(Color::DarkGray),
+                        )
+                    }),
+            );
+        }
+
+        lines
+    }
+
+    /// The main view's border color, showing at a glance whether the clock is running.
+    fn border_color(&self) -> Color {
+        match self.state {
+            AppState::Working | AppState::Logs if self.paused => self.theme.border_paused,
+            AppState::Working => self.theme.border_working,
+            AppState::Logs => self.theme.border_logs,
+            _ => self.theme.border_menu,
+        }
+    }
+
+    /// The clock-out summary screen: the same total and log count the clock-out message has.
+    fn session_summary_lines(&self) -> Vec<Line<'_>> {
+        let Some(summary) = &self.session_summary else {
+            return vec![];
+        };
+
+        let mut lines = vec![
+            Line::from(format!("Goodbye, {}!", self.username)),
+            Line::from(""),
+        ];
+
+        if let Some(project) = &summary.project {
+            lines.push(Line::from(format!("Project: {}", project)));
+        }
+
+        lines.push(Line::from(format!(
+            "Total Worked Time: {}",
+            self.verbose_format().format(summary.worked)
+        )));
+        lines.push(Line::from(format!("Logs: {}", summary.logs)));
+
+        if let Some(note) = &summary.note {
+            lines.push(Line::from(""));
+            lines.push(Line::from(note.as_str()));
+        }
+
+        lines
+    }
+
+    /// Time left until each timed side effect that is currently active, e.g.
+    /// `Next heartbeat in 12:30`.
+    fn countdowns(&self) -> Vec<String> {
+        let mut countdowns = vec![];
+
+        if let Some(interval) = self.heartbeat_interval
+            && !self.paused
+            && self.webhooks_enabled()
+        {
+            let interval = interval.as_secs().max(1) as usize;
+            let remaining = interval - self.elapsed_secs() % interval;
+
+            countdowns.push(format!("Next heartbeat in {}", format_compact(remaining)));
+        }
+
+        let autosave = AUTOSAVE_INTERVAL.saturating_sub(self.autosaved_at.elapsed());
+        countdowns.push(format!(
+            "Autosave in {}",
+            format_compact(autosave.as_secs() as usize)
+        ));
+
+        countdowns
+    }
+
+    /// Renders every action and its bound keys in a box centered over the current view.
+    fn draw_help(&self, frame: &mut Frame, area: Rect) {
+        let lines = Action::ALL
+            .iter()
+            .map(|&action| {
+                Line::from(format!(
+                    "{:>16}  {}",
+                    self.keymap.label(action),
+                    action.description()
+                ))
+            })
+            .collect::<Vec<Line>>();
+
+        let help_area = centered(area, 50, lines.len() as u16 + 2);
+
+        frame.render_widget(Clear, help_area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Help")
+                    .title(Line::from(" Esc - Close ").right_aligned()),
+            ),
+            help_area,
+        );
+    }
+
+    fn draw_resume_prompt(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = vec![Line::from("An unfinished session was found.")];
+
+        if let Some(clocked_in_at) = self.clocked_in_at {
+            lines.push(Line::from(format!(
+                "Clocked in {}",
+                clocked_in_at.format("%m/%d/%Y %H:%M")
+            )));
+        }
+
+        lines.extend([
+            Line::from(format!(
+                "{} worked, {} {}",
+                self.get_compact_time(),
+                self.logs.len(),
+                if self.logs.len() == 1 { "log" } else { "logs" }
+            )),
+            Line::from(""),
+            Line::from("R/Enter - Resume"),
+            Line::from("F - Start Fresh (archive it)"),
+        ]);
+
+        let prompt_area = centered(area, 40, lines.len() as u16 + 2);
+
+        frame.render_widget(Clear, prompt_area);
+        frame.render_widget(
+            Paragraph::new(lines).alignment(Alignment::Center).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Resume Session?"),
+            ),
+            prompt_area,
+        );
+    }
+
+    /// What a confirmed clock out will post, e.g. `4 logs, 2h 14m will be sent`.
+    fn clock_out_preview(&self) -> String {
+        format!(
+            "{} {}, {} will be {}",
+            self.logs.len(),
+            if self.logs.len() == 1 { "log" } else { "logs" },
+            TimeFormat::Short.format(self.worked_secs()),
+            if self.webhooks_enabled() {
+                "sent"
+            } else {
+                "saved"
+            }
+        )
+    }
+
+    fn draw_clock_out_confirmation(&self, frame: &mut Frame, area: Rect) {
+        let lines = vec![
+            Line::from(self.clock_out_preview()),
+            Line::from(""),
+            Line::from("Y/Enter - Clock Out"),
+            Line::from("N/Esc - Cancel"),
+        ];
+
+        let confirm_area = centered(area, 40, lines.len() as u16 + 2);
+
+        frame.render_widget(Clear, confirm_area);
+        frame.render_widget(
+            Paragraph::new(lines).alignment(Alignment::Center).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Clock Out?"),
+            ),
+            confirm_area,
+        );
+    }
+
+    fn draw_quit_confirmation(&self, frame: &mut Frame, area: Rect) {
+        let lines = vec![
+            Line::from(if self.clocked_in() {
+                "You are still clocked in."
+            } else {
+                "You have logs on the list."
+            }),
+            Line::from(""),
+            Line::from("S - Save and Quit"),
+            Line::from("D - Discard and Quit"),
+            Line::from("C/Esc - Cancel"),
+        ];
+
+        let confirm_area = centered(area, 36, lines.len() as u16 + 2);
+
+        frame.render_widget(Clear, confirm_area);
+        frame.render_widget(
+            Paragraph::new(lines).alignment(Alignment::Center).block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("Quit?"),
+            ),
+            confirm_area,
+        );
+    }
+
+    /// The title of the open prompt, for prompts that take typed text.
+    fn prompt_title(&self) -> Option<&'static str> {
+        match self.prompt_state {
+            PromptState::Input => Some("Input"),
+            PromptState::Edit => Some("Edit"),
+            PromptState::Filter => Some("Filter"),
+            PromptState::Setting => Some(Setting::ALL[self.selected_setting].label()),
+            PromptState::SessionNote => Some("Session Note"),
+            PromptState::AdjustTime => Some("Adjust Time (+10m / -5m)"),
+            PromptState::Confirm | PromptState::NoPrompt => None,
+        }
+    }
+
+    /// The one line shown in compact mode, e.g. `⏱ Working 1:23:45 · 4 logs`. Open prompts and
+    /// notices take its place the same way they do in the full layout.
+    fn compact_line(&self) -> Line<'_> {
+        if let Some(title) = self.prompt_title() {
+            return Line::from(format!("{}: {}", title, self.prompt_input.value()));
+        }
+
+        if self.prompt_state == PromptState::Confirm {
+            return Line::from("Delete this log? (y/n)");
+        }
+
+        if self.confirm_clock_out {
+            return Line::from(format!("Clock out? {} (y/n)", self.clock_out_preview()));
+        }
+
+        if let Some(notice) = self.focus_lock_notice().or_else(|| self.notice()) {
+            return Line::styled(
+                notice.trim().to_string(),
+                Style::new().fg(self.theme.paused),
+            );
+        }
+
+        let logs = format!(
+            "{} {}",
+            self.logs.len(),
+            if self.logs.len() == 1 { "log" } else { "logs" }
+        );
+
+        match self.state {
+            AppState::Working | AppState::Logs if self.paused => Line::styled(
+                format!("⏸ Paused {} · {}", self.get_compact_time(), logs),
+                Style::new().fg(self.theme.paused),
+            ),
+            AppState::Working | AppState::Logs => {
+                Line::from(format!("⏱ Working {} · {}", self.get_compact_time(), logs))
+            }
+            AppState::Menu => Line::from(format!(
+                "⏱ Clocked out · Today {}",
+                self.time_format
+                    .unwrap_or(TimeFormat::Short)
+                    .format(self.worked_today() as usize)
+            )),
+            AppState::Summary => Line::from(match &self.session_summary {
+                Some(summary) => format!(
+                    "⏱ Clocked out after {} · {} {}",
+                    self.time_format
+                        .unwrap_or(TimeFormat::Clock)
+                        .format(summary.worked),
+                    summary.logs,
+                    if summary.logs == 1 { "log" } else { "logs" }
+                ),
+                None => "⏱ Clocked out".to_string(),
+            }),
+            AppState::DaySummary => Line::from("⏱ Today's Summary"),
+            AppState::Week => Line::from("⏱ Week"),
+            AppState::SelectProject => Line::from(format!(
+                "⏱ Project: {}",
+                self.projects
+                    .get(self.selected_project)
+                    .map_or("", String::as_str)
+            )),
+            AppState::Settings => Line::from("⏱ Settings"),
+        }
+    }
+
+    fn draw_compact(&self, frame: &mut Frame) {
+        let area = frame.area();
+
+        frame.render_widget(
+            Paragraph::new(self.compact_line()).style(Style::new().fg(self.theme.text)),
+            area,
+        );
+
+        if let Some(title) = self.prompt_title()
+            && let (0, column) = cursor_row_column(&self.prompt_input)
+        {
+            let x = area.x + Span::raw(title).width() as u16 + 2 + column;
+            frame.set_cursor_position((x.min(area.right().saturating_sub(1)), area.y));
+        }
+
+        if self.show_help {
+            self.draw_help(frame, area);
+        }
+
+        if self.confirm_resume {
+            self.draw_resume_prompt(frame, area);
+        }
+
+        if self.confirm_quit {
+            self.draw_quit_confirmation(frame, area);
+        }
+
+        if self.confirm_clock_out {
+            self.draw_clock_out_confirmation(frame, area);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+
+        if self.compact {
+            self.draw_compact(frame);
+            return;
+        }
+
+        if area.height < MIN_HEIGHT || area.width < MIN_WIDTH {
+            frame.render_widget(
+                Paragraph::new("Terminal too small").alignment(Alignment::Center),
+                area,
+            );
+            return;
+        }
+
+        let prompt_open = self.prompt_state != PromptState::NoPrompt;
+        // Multiline logs grow the prompt up to five lines of text.
+        let prompt_height = self.prompt_input.value().split('\n').count().min(5) as u16 + 2;
+        let show_controls = area.height >= if prompt_open { 6 + prompt_height } else { 6 };
+
+        let title = match self.state {
+            AppState::Menu => "Menu".to_string(),
+            AppState::Summary => "Clocked Out".to_string(),
+            AppState::DaySummary => "Today".to_string(),
+            AppState::Week => "Week".to_string(),
+            AppState::SelectProject => "Select Project".to_string(),
+            AppState::Settings => "Settings".to_string(),
+            AppState::Working => "Working".to_string(),
+            AppState::Logs if !self.log_jump.is_empty() => {
+                format!("Logs (go to: {})", self.log_jump)
+            }
+            AppState::Logs if self.log_filter.is_empty() => "Logs".to_string(),
+            AppState::Logs => format!("Logs (filter: {})", self.log_filter),
+        };
+
+        let mut constraints = vec![Constraint::Min(0)];
+
+        if prompt_open {
+            constraints.push(Constraint::Length(prompt_height));
+        }
+
+        if show_controls {
+            constraints.push(Constraint::Length(3));
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.scroll_to_selected(
+            chunks[0].height.saturating_sub(2) as usize,
+            chunks[0].width.saturating_sub(2),
+        );
+
+        let scroll = match self.state {
+            AppState::Logs => self.log_scroll as u16,
+            _ => 0,
+        };
+
+        let view = match self.state {
+            AppState::Menu => Paragraph::new(
+                self.banner
+                    .as_deref()
+                    .map(banner_lines)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain([
+                        Line::from(format!("Welcome To WorkWatch, {}", self.username)),
+                        Line::from(""),
+                        Line::from(format!(
+                            "Today: {}",
+                            self.time_format
+                                .unwrap_or(TimeFormat::Short)
+                                .format(self.worked_today() as usize)
+                        )),
+                    ])
+                    .chain((!self.webhooks_enabled()).then(|| {
+                        Line::styled(
+                            "Webhook: not configured, nothing will be posted",
+                            Style::new().fg(Color::DarkGray),
+                        )
+                    }))
+                    .collect::<Vec<Line>>(),
+            ),
+            AppState::Summary => Paragraph::new(self.session_summary_lines()),
+            AppState::DaySummary => Paragraph::new(vec![
+                Line::from(format!("Summary for {}", Local::now().format("%m/%d/%Y"))),
+                Line::from(""),
+                Line::from(format!(
+                    "Total Worked Time: {}",
+                    self.verbose_format()
+                        .format(self.day_summary.seconds as usize)
+                )),
+                Line::from(format!("Sessions: {}", self.day_summary.sessions)),
+                Line::from(format!("Logs: {}", self.day_summary.logs)),
+            ]),
+            AppState::Week => Paragraph::new(vec![Line::from(format!(
+                "Week of {} | Total: {} | Streak: {} {}",
+                self.week_summary.monday.format("%m/%d/%Y"),
+                format_compact(self.week_summary.total() as usize),
+                self.streak,
+                if self.streak == 1 { "day" } else { "days" }
+            ))]),
+            AppState::SelectProject => Paragraph::new(
+                self.projects
+                    .iter()
+                    .enumerate()
+                    .map(|(index, project)| {
+                        let text = format!(
+                            "{} (this week: {})",
+                            project,
+                            format_compact(self.project_totals.this_week(project) as usize)
+                        );
+
+                        if index == self.selected_project {
+                            Line::from(Span::styled(
+                                text,
+                                Style::new()
+                                    .fg(self.theme.selected)
+                                    .add_modifier(Modifier::BOLD),
+                            ))
+                        } else {
+                            Line::from(text)
+                        }
+                    })
+                    .collect::<Vec<Line>>(),
+            ),
+            AppState::Settings => Paragraph::new(
+                Setting::ALL
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &setting)| {
+                        let value = match self.setting_value(setting) {
+                            "" => "(not set)",
+                            value => value,
+                        };
+                        let text = format!("{}: {}", setting.label(), value);
+
+                        if index == self.selected_setting {
+                            Line::from(Span::styled(
+                                text,
+                                Style::new()
+                                    .fg(self.theme.selected)
+                                    .add_modifier(Modifier::BOLD),
+                            ))
+                        } else {
+                            Line::from(text)
+                        }
+                    })
+                    .collect::<Vec<Line>>(),
+            ),
+            AppState::Working => Paragraph::new(self.working_lines()),
+            AppState::Logs => Paragraph::new(if self.presenting {
+                vec![Line::from(format!(
+                    "Logs hidden ({} to show)",
+                    self.keymap.label(Action::Presentation)
+                ))]
+            } else if self.logs.is_empty() {
+                vec![Line::from("No Logs Yet")]
+            } else if self.selected_log.is_none() {
+                vec![Line::from("No Matching Logs")]
+            } else {
+                self.visible_logs()
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(position, index)| self.log_lines(position, index))
+                    .collect::<Vec<Line>>()
+            }),
+        }
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(if self.pomodoro_flashing() {
+                    Style::new().fg(Color::LightRed)
+                } else {
+                    Style::new().fg(self.border_color())
+                })
+                .title(title)
+                .title(match self.state {
+                    AppState::Logs if self.clocked_in() => {
+                        let elapsed = format!(" {} ", self.get_compact_time());
+
+                        if self.paused {
+                            Line::styled(elapsed, Style::new().fg(self.theme.paused))
+                                .right_aligned()
+                        } else {
+                            Line::from(elapsed).right_aligned()
+                        }
+                    }
+                    _ => Line::default(),
+                })
+                .title_bottom(match self.state {
+                    AppState::Logs => Line::from(self.log_stats()).right_aligned(),
+                    _ => Line::default(),
+                }),
+        )
+        .style(Style::new().fg(self.theme.text))
+        .alignment(Alignment::Center)
+        .scroll((scroll, 0));
+
+        frame.render_widget(
+            match self.state {
+                AppState::Logs => view.wrap(Wrap { trim: false }),
+                _ => view,
+            },
+            chunks[0],
+        );
+
+        // The chart fills the Week view below its header line.
+        if let AppState::Week = self.state {
+            let inner = chunks[0].inner(Margin::new(1, 1));
+            let chart_area = Rect::new(
+                inner.x,
+                inner.y + 1,
+                inner.width,
+                inner.height.saturating_sub(1),
+            );
+
+            frame.render_widget(self.week_chart(chart_area.width), chart_area);
+        }
+
+        // The gauge sits on the bottom row inside the Working view's border.
+        if let (AppState::Working, Some(goal)) = (self.state, self.daily_goal)
+            && chunks[0].height >= 7
+        {
+            let inner = chunks[0].inner(Margin::new(2, 1));
+            let gauge_area = Rect::new(inner.x, inner.bottom() - 1, inner.width, 1);
+
+            frame.render_widget(self.goal_gauge(goal), gauge_area);
+        }
+
+        match self.prompt_title() {
+            Some(title) => {
+                let area = chunks[1];
+                let (row, column) = cursor_row_column(&self.prompt_input);
+                // Scroll just far enough to keep the cursor inside the border.
+                let scroll = (
+                    row.saturating_sub(area.height.saturating_sub(3)),
+                    column.saturating_sub(area.width.saturating_sub(3)),
+                );
+
+                frame.render_widget(
+                    Paragraph::new(self.prompt_input.to_string())
+                        .scroll(scroll)
+                        .block(
+                            Block::bordered()
+                                .border_type(BorderType::Rounded)
+                                .title(title),
+                        ),
+                    area,
+                );
+                frame.set_cursor_position((
+                    area.x + 1 + column - scroll.1,
+                    area.y + 1 + row - scroll.0,
+                ));
+            }
+            None if self.prompt_state == PromptState::Confirm => {
+                frame.render_widget(
+                    Paragraph::new("Delete this log? (y/n)").block(
+                        Block::bordered()
+                            .border_type(BorderType::Rounded)
+                            .title("Confirm"),
+                    ),
+                    chunks[1],
+                );
+            }
+            None => {}
+        }
+
+        if show_controls {
+            frame.render_widget(
+                Paragraph::new(vec![
+                    match self.focus_lock_notice().or_else(|| self.notice()) {
+                        Some(notice) => Line::styled(notice, Style::new().fg(self.theme.paused)),
+                        None => Line::from(match self.state {
+                            AppState::Menu => self.controls(&[
+                                (Action::ClockIn, "Clock In"),
+                                (Action::Summary, "Today's Summary"),
+                                (Action::Week, "Week"),
+                                (Action::Settings, "Settings"),
+                                (Action::Help, "Help"),
+                                (Action::Quit, "Quit"),
+                            ]),
+                            AppState::Summary => match &self.session_summary {
+                                Some(summary) => format!(
+                                    " Any Key - Back to Menu ({}s) ",
+                                    self.summary_duration
+                                        .unwrap_or_default()
+                                        .saturating_sub(summary.shown_at.elapsed())
+                                        .as_secs()
+                                        + 1
+                                ),
+                                None => " Any Key - Back to Menu ".to_string(),
+                            },
+                            AppState::DaySummary => {
+                                format!(" Esc/{} - Back ", self.keymap.label(Action::Summary))
+                            }
+                            AppState::Week => format!(
+                                " Left - Previous Week | Right - Next Week | Esc/{} - Back ",
+                                self.keymap.label(Action::Week)
+                            ),
+                            AppState::SelectProject => format!(
+                                " {} - Previous | {} - Next | Enter - Clock In | Esc - Back ",
+                                self.keymap.label(Action::Up),
+                                self.keymap.label(Action::Down)
+                            ),
+                            AppState::Settings => format!(
+                                " {} - Previous | {} - Next | Enter/{} - Edit | Esc/{} - Back ",
+                                self.keymap.label(Action::Up),
+                                self.keymap.label(Action::Down),
+                                self.keymap.label(Action::EditLog),
+                                self.keymap.label(Action::Settings)
+                            ),
+                            AppState::Working => self.controls(&[
+                                (Action::ViewLogs, "View Logs"),
+                                (Action::AddLog, "Add Log"),
+                                (Action::Pause, if self.paused { "Resume" } else { "Pause" }),
+                                (Action::Pomodoro, "Pomodoro"),
+                                (Action::SessionNote, "Note"),
+                                (Action::ClockOut, "Clock Out"),
+                            ]),
+                            AppState::Logs => self.controls(&[
+                                (Action::ViewTime, "View Time"),
+                                (Action::AddLog, "Add Log"),
+                                (Action::EditLog, "Edit Log"),
+                                (Action::DeleteLog, "Delete Log"),
+                                (Action::Undo, "Undo"),
+                                (Action::Filter, "Filter"),
+                                (Action::ClockOut, "Clock Out"),
+                            ]),
+                        }),
+                    },
+                ])
+                .block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .title("Controls")
+                        .title_bottom(match self.storage_error {
+                            Some(_) => {
+                                Line::styled(" ⚠ not saving ", Style::new().fg(Color::LightRed))
+                            }
+                            None => Line::default(),
+                        })
+                        .title(
+                            match *self.webhook_status.lock().unwrap() {
+                                WebhookStatus::Idle if !self.webhooks_enabled() => {
+                                    Line::styled(" solo ", Style::new().fg(Color::DarkGray))
+                                }
+                                WebhookStatus::Idle => Line::default(),
+                                WebhookStatus::Sending => {
+                                    Line::styled(" … sending ", Style::new().fg(Color::Yellow))
+                                }
+                                WebhookStatus::Sent(_) => {
+                                    Line::styled(" ✓ sent ", Style::new().fg(Color::LightGreen))
+                                }
+                                WebhookStatus::Failed(_) => {
+                                    Line::styled(" ✗ failed ", Style::new().fg(Color::LightRed))
+                                }
+                            }
+                            .right_aligned(),
+                        ),
+                ),
+                chunks[chunks.len() - 1],
+            );
+        }
+
+        if self.show_help {
+            self.draw_help(frame, area);
+        }
+
+        if self.confirm_resume {
+            self.draw_resume_prompt(frame, area);
+        }
+
+        if self.confirm_quit {
+            self.draw_quit_confirmation(frame, area);
+        }
+
+        if self.confirm_clock_out {
+            self.draw_clock_out_confirmation(frame, area);
+        }
+    }
+
+    /// Every webhook URL to post to, with its flavor: the current project's webhook when
+    /// `project_webhooks` has one, otherwise each URL in `webhook_url`.
+    fn webhook_routes(&self) -> Vec<(&str, WebhookFlavor)> {
+        let project_webhook = self
+            .project
+            .as_ref()
+            .and_then(|project| self.project_webhooks.get(project));
+
+        let mut routes = match project_webhook {
+            Some(webhook_url) => vec![(webhook_url.as_str(), WebhookFlavor::detect(webhook_url))],
+            None => webhook_urls(&self.webhook_url)
+                .map(|url| {
+                    (
+                        url,
+                        self.webhook_flavor
+                            .unwrap_or_else(|| WebhookFlavor::detect(url)),
+                    )
+                })
+                .collect(),
+        };
+
+        // Dry-run mode writes payloads out even without a webhook URL.
+        if routes.is_empty() && self.dry_run.is_some() {
+            routes.push(("", self.webhook_flavor.unwrap_or(WebhookFlavor::Discord)));
+        }
+
+        routes
+    }
+
+    fn webhooks_enabled(&self) -> bool {
+        !self.webhook_routes().is_empty()
+    }
+
+    /// Queues `payload` for the webhook worker, which posts it (or writes it out in dry-run
+    /// mode) after everything queued before it.
+    fn send_webhook(&self, build: impl Fn(&Sender) -> Value) {
+        self.queue_webhook(build, None);
+    }
+
+    /// Queues one message per destination, each built by `build` in that destination's flavor.
+    /// Attachments only go to Discord.
+    fn queue_webhook(&self, build: impl Fn(&Sender) -> Value, attachment: Option<Attachment>) {
+        for (url, flavor) in self.webhook_routes() {
+            let job = WebhookJob {
+                url: url.to_string(),
+                payload: build(&self.sender(flavor)),
+                attachment: attachment
+                    .clone()
+                    .filter(|_| flavor == WebhookFlavor::Discord),
+            };
+
+            tracing::debug!(host = webhook_host(&job.url), "webhook queued");
+
+            if let Some(queue) = &self.webhook_queue
+                && queue.send(job).is_ok()
+            {
+                *self.webhook_status.lock().unwrap() = WebhookStatus::Sending;
+            }
+        }
+    }
+
+    /// Closes the queue and gives the worker up to `WEBHOOK_SHUTDOWN_TIMEOUT` to deliver what's
+    /// left, so the clock-out message isn't dropped when the app exits right after it.
+    pub async fn finish_webhooks(&mut self) {
+        self.webhook_queue = None;
+
+        if let Some(worker) = self.webhook_worker.take() {
+            let _ = tokio::time::timeout(WEBHOOK_SHUTDOWN_TIMEOUT, worker).await;
+        }
+    }
+
+    fn sender(&self, flavor: WebhookFlavor) -> Sender<'_> {
+        Sender {
+            flavor,
+            mentions: self.mentions,
+            bot_name: &self.bot_name,
+            username: &self.username,
+            messages: &self.messages,
+            time_format: self.verbose_format(),
+            timezone: self.timezone,
+        }
+    }
+
+    fn send_clock_in_webhook(&mut self) {
+        if !self.webhooks_enabled() || !self.notify.clock_in {
+            return;
+        }
+
+        self.send_webhook(|sender| {
+            build_clock_in_payload(
+                sender,
+                self.project.as_deref(),
+                // The message may have been held back by `min_session`.
+                self.clocked_in_at.unwrap_or_else(Local::now),
+            )
+        });
+    }
+
+    fn send_checkpoint_webhook(&mut self) {
+        if !self.webhooks_enabled() {
+            return;
+        }
+
+        self.send_webhook(|sender| {
+            build_checkpoint_payload(
+                sender,
+                self.project.as_deref(),
+                Local::now(),
+                self.worked_time(),
+                &self.logs,
+            )
+        });
+    }
+
+    fn send_clock_out_webhook(&mut self, note: Option<&str>) {
+        if !self.webhooks_enabled() || !self.notify.clock_out {
+            return;
+        }
+
+        let now = Local::now();
+        let attachment = self.clock_out_attachment(note);
+
+        self.queue_webhook(
+            |sender| {
+                build_clock_out_payload(
+                    sender,
+                    self.project.as_deref(),
+                    self.session_note.as_deref(),
+                    now,
+                    self.worked_time(),
+                    &self.logs,
+                    note,
+                )
+            },
+            attachment,
+        );
+    }
+
+    /// Seconds worked so far, leaving out logged breaks. Displayed and reported totals use this,
+    /// while log timestamps and the timed features follow `elapsed_secs`.
+    fn worked_secs(&self) -> usize {
+        self.worked_time().total
+    }
+
+    /// The worked total alongside the breaks left out of it, for messages that show both.
+    fn worked_time(&self) -> WorkedTime {
+        let breaks = self.session_break_secs();
+
+        WorkedTime {
+            total: self.elapsed_secs().saturating_sub(breaks),
+            breaks,
+        }
+    }
+
+    /// Length of the session's breaks, including those in logs cleared by a checkpoint.
+    fn session_break_secs(&self) -> usize {
+        break_secs(&self.checkpointed_logs).saturating_add(break_secs(&self.logs))
+    }
+
+    /// Seconds since clocking in: the accumulated base offset plus the running segment, if any.
+    fn elapsed_secs(&self) -> usize {
+        self.base_seconds.saturating_add(self.segment_secs())
+    }
+
+    /// Seconds in the running segment, which restarts on launch and on every resume.
+    fn segment_secs(&self) -> usize {
+        self.started_at
+            .map_or(0, |started_at| started_at.elapsed().as_secs() as usize)
+    }
+
+    /// Seconds worked since the app was relaunched into a restored session.
+    fn secs_since_restore(&self) -> Option<usize> {
+        self.restored_seconds
+            .map(|restored| self.elapsed_secs().saturating_sub(restored))
+    }
+
+    /// Number of heartbeat interval boundaries the current session's elapsed time has crossed.
+    fn heartbeats_due(&self) -> usize {
+        self.heartbeat_interval.map_or(0, |interval| {
+            self.elapsed_secs() / interval.as_secs().max(1) as usize
+        })
+    }
+
+    /// Sends the held-back clock-in message once the session is at least `min_session` long,
+    /// or right away without one.
+    fn send_pending_clock_in(&mut self) {
+        let long_enough = self
+            .min_session
+            .is_none_or(|min_session| self.elapsed_secs() as u64 >= min_session.as_secs());
+
+        if self.clock_in_pending && long_enough {
+            self.clock_in_pending = false;
+            self.send_clock_in_webhook();
+        }
+    }
+
+    fn send_due_heartbeat(&mut self) {
+        if !self.clocked_in() || self.paused {
+            return;
+        }
+
+        let due = self.heartbeats_due();
+
+        if due > self.heartbeats_sent {
+            self.heartbeats_sent = due;
+            self.send_heartbeat_webhook();
+        }
+    }
+
+    fn send_heartbeat_webhook(&mut self) {
+        if !self.webhooks_enabled() {
+            return;
+        }
+
+        let title = format!("{} is still working!", self.username);
+        let description = format!("\nElapsed Time: {}", self.get_verbose_time());
+
+        self.send_webhook(|sender| build_payload(sender, &title, &description, 0x00bfff));
+    }
+
+    fn send_pause_webhook(&mut self) {
+        if !self.webhooks_enabled() || !self.notify.pause {
+            return;
+        }
+
+        let title = if self.paused {
+            format!("{} is taking a break!", self.username)
+        } else {
+            format!("{} is back to work!", self.username)
+        };
+        let now = self.timezone.convert(Local::now());
+        let date = now.format("%m/%d/%Y").to_string();
+        let time = now.format("%H:%M:%S (UTC%z)").to_string();
+        let description = format!(
+            "\nDate: {}\nTime: {}\n\nLogged Time So Far: {}",
+            date,
+            time,
+            self.get_verbose_time()
+        );
+
+        self.send_webhook(|sender| build_payload(sender, &title, &description, 0xffcc00));
+    }
+
+    fn send_goal_webhook(&mut self, goal: Duration) {
+        if !self.webhooks_enabled() {
+            return;
+        }
+
+        let title = format!("{} reached their daily goal!", self.username);
+        let description = format!(
+            "\nWorked {} today, meeting the goal of {}.",
+            self.verbose_format().format(self.worked_today() as usize),
+            self.verbose_format().format(goal.as_secs() as usize)
+        );
+
+        self.send_webhook(|sender| build_payload(sender, &title, &description, 0xffd700));
+    }
+
+    fn send_new_day_webhook(&mut self, yesterday: u64) {
+        if !self.webhooks_enabled() || !self.notify.new_day {
+            return;
+        }
+
+        let title = format!("{} is working into a new day!", self.username);
+        let description = format!(
+            "\nWorked {} yesterday in this session, still clocked in.",
+            self.verbose_format().format(yesterday as usize)
+        );
+
+        self.send_webhook(|sender| build_payload(sender, &title, &description, 0x5865f2));
+    }
+
+    fn send_pomodoro_webhook(&mut self, finished: PomodoroPhase) {
+        if !self.webhooks_enabled() {
+            return;
+        }
+
+        let (title, description) = match finished {
+            PomodoroPhase::Work => (
+                format!("{} finished a focus interval!", self.username),
+                format!(
+                    "\nTaking a {} minute break.",
+                    self.pomodoro_config.break_length.as_secs() / 60
+                ),
+            ),
+            PomodoroPhase::Break => (
+                format!("{} is back from a break!", self.username),
+                format!(
+                    "\nStarting a {} minute focus interval.",
+                    self.pomodoro_config.work_length.as_secs() / 60
+                ),
+            ),
+        };
+
+        self.send_webhook(|sender| build_payload(sender, &title, &description, 0xff6347));
+    }
+
+    fn get_compact_time(&self) -> String {
+        self.time_format
+            .unwrap_or(TimeFormat::Clock)
+            .format(self.worked_secs())
+    }
+
+    fn get_verbose_time(&self) -> String {
+        self.verbose_format().format(self.worked_secs())
+    }
+
+    /// The format for durations written out in messages and exports.
+    fn verbose_format(&self) -> TimeFormat {
+        self.time_format.unwrap_or(TimeFormat::Verbose)
+    }
+}
+
+/// The row and column of `input`'s cursor within its text, in terminal cells. Alt+Enter line
+/// breaks start a new row.
+fn cursor_row_column(input: &Input) -> (u16, u16) {
+    let before = input
+        .value()
+        .chars()
+        .take(input.cursor())
+        .collect::<String>();
+    let row = before.matches('\n').count();
+    let column = Span::raw(before.rsplit('\n').next().unwrap_or_default()).width();
+
+    (row as u16, column as u16)
+}
+
+/// The banner's lines padded to one width, so centering each line keeps the art lined up, with a
+/// blank line after it.
+fn banner_lines(art: &str) -> Vec<Line<'static>> {
+    let width = art
+        .lines()
+        .map(|line| Span::raw(line).width())
+        .max()
+        .unwrap_or(0);
+
+    art.lines()
+        .map(|line| {
+            let padding = width - Span::raw(line).width();
+            Line::from(format!("{}{}", line, " ".repeat(padding)))
+        })
+        .chain([Line::from("")])
+        .collect()
+}
+
+/// A `width` x `height` rectangle in the middle of `area`, shrunk to fit if needed.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = area.width.min(width);
+    let height = area.height.min(height);
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// The position `step` entries away from `position` in a list of `len`, wrapping around at
+/// either end or stopping there. `None` for an empty list, which has nothing to select.
+fn step_position(position: usize, len: usize, step: isize, wrap: bool) -> Option<usize> {
+    let last = len.checked_sub(1)? as isize;
+    let target = position.min(len - 1) as isize + step;
+
+    Some(if wrap {
+        target.rem_euclid(last + 1)
+    } else {
+        target.clamp(0, last)
+    } as usize)
+}
+
+/// Removes every entry whose tags and text match the entry right before it, returning the
+/// removed entries with their original indices.
+fn remove_consecutive_duplicates(logs: &mut Vec<LogEntry>) -> Vec<(usize, LogEntry)> {
+    let mut removed = vec![];
+    let mut kept: Vec<LogEntry> = Vec::with_capacity(logs.len());
+
+    for (index, log) in logs.drain(..).enumerate() {
+        match kept.last() {
+            Some(previous) if previous.input() == log.input() => removed.push((index, log)),
+            _ => kept.push(log),
+        }
+    }
+
+    *logs = kept;
+    removed
+}
+
+/// Total length of the `#break` entries in `logs`.
+fn break_secs(logs: &[LogEntry]) -> usize {
+    logs.iter()
+        .filter_map(|log| log.break_length)
+        .map(|length| length.as_secs() as usize)
+        .fold(0, usize::saturating_add)
+}
+
+/// Whether `clock_out_at` has passed today for a session clocked in at `clocked_in_at`. Sessions
+/// started after today's deadline run until tomorrow's.
+fn scheduled_clock_out_due(
+    clocked_in_at: DateTime<Local>,
+    now: DateTime<Local>,
+    clock_out_at: NaiveTime,
+) -> bool {
+    let deadline = now.date_naive().and_time(clock_out_at);
+
+    now.naive_local() >= deadline && clocked_in_at.naive_local() < deadline
+}
+
+/// Splits a session at every midnight in `splits`, giving each day its own record with the time
+/// and logs from that day. The note stays with the last one.
+fn split_by_day(
+    session: SessionRecord,
+    logs: &[LogEntry],
+    splits: &[DaySplit],
+) -> Vec<SessionRecord> {
+    // Logs without a creation time are from before it was recorded; they go with the last day.
+    let day = |log: &LogEntry| {
+        log.created_at.map_or(splits.len(), |at| {
+            splits.iter().filter(|split| split.at <= at).count()
+        })
+    };
+    let logs_on = |index: usize| {
+        logs.iter()
+            .filter(|log| day(log) == index)
+            .map(LogEntry::input)
+            .collect()
+    };
+
+    let mut records = vec![];
+    let mut start = (session.clocked_in_at, 0);
+
+    for (index, split) in splits.iter().enumerate() {
+        records.push(SessionRecord {
+            clocked_in_at: start.0,
+            clocked_out_at: split.at,
+            seconds: split.seconds.saturating_sub(start.1),
+            project: session.project.clone(),
+            logs: logs_on(index),
+            note: None,
+        });
+        start = (split.at, split.seconds);
+    }
+
+    records.push(SessionRecord {
+        clocked_in_at: start.0,
+        seconds: session.seconds.saturating_sub(start.1),
+        logs: logs_on(splits.len()),
+        ..session
+    });
+    records
+}
+
+/// The `--json-summary` output for a finished session, using the same field names as
+/// `--export json` plus the logs themselves.
+fn json_summary(session: &SessionRecord, logs: &[LogEntry]) -> Value {
+    json!({
+        "clock_in": session.clocked_in_at.to_rfc3339(),
+        "clock_out": session.clocked_out_at.to_rfc3339(),
+        "total_seconds": session.seconds,
+        "project": session.project,
+        "note": session.note,
+        "log_count": logs.len(),
+        "logs": logs
+            .iter()
+            .map(|log| json!({
+                "at": log.at.as_secs(),
+                "created_at": log.created_at.map(|at| at.to_rfc3339()),
+                "text": log.input(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// A short duration for log deltas, e.g. `45s`, `8m` or `1h 5m`.
+fn format_delta(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3_600 => format!("{}m", secs / 60),
+        _ => format!("{}h {}m", secs / 3_600, (secs / 60) % 60),
+    }
+}
+
+/// A signed `format_delta`, e.g. `+10m` or `-1h 5m`.
+fn format_adjustment(delta: i64) -> String {
+    format!(
+        "{}{}",
+        if delta < 0 { "-" } else { "+" },
+        format_delta(delta.unsigned_abs())
+    )
+}
+
+fn clock_out_description(
+    now: DateTime<Local>,
+    timezone: Timezone,
+    worked: WorkedTime,
+    time_format: TimeFormat,
+    session_note: Option<&str>,
+    logs: &[LogEntry],
+    note: Option<&str>,
+) -> String {
+    let now = timezone.convert(now);
+    let date = now.format("%m/%d/%Y").to_string();
+    let time = now.format("%H:%M:%S (UTC%z)").to_string();
+    let mut description = "\n".to_string();
+
+    if let Some(session_note) = session_note {
+        description.push_str(&format!("Session Note: {}\n\n", session_note));
+    }
+
+    description.push_str(&format!(
+        "Date: {}\nTime: {}\n\nTotal Logged Time: {}\n",
+        date,
+        time,
+        time_format.format(worked.total)
+    ));
+
+    match worked.breaks {
+        0 => description.push('\n'),
+        breaks => description.push_str(&format!("Breaks: {}\n\n", time_format.format(breaks))),
+    }
+
+    if let Some(note) = note {
+        description.push_str(&format!("Note: {}\n\n", note));
+    }
+
+    if logs.is_empty() {
+        description.push_str("No logs to display.");
+    } else {
+        description.push_str("Logs:\n");
+        description.push_str(
+            logs.iter()
+                .map(|log| log.stamped(timezone))
+                .collect::<Vec<String>>()
+                .join("\n")
+                .as_str(),
+        );
+
+        let mut categories = BTreeMap::<&str, usize>::new();
+
+        for tag in logs.iter().flat_map(|log| &log.tags) {
+            *categories.entry(tag.as_str()).or_default() += 1;
+        }
+
+        if !categories.is_empty() {
+            description.push_str("\n\nCategories: ");
+            description.push_str(
+                categories
+                    .iter()
+                    .map(|(tag, count)| format!("#{} ({})", tag, count))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+                    .as_str(),
+            );
+        }
+    };
+
+    description
+}
+
+/// Who a webhook message is posted as, how its title is worded and the schema it's shaped into.
+struct Sender<'a> {
+    flavor: WebhookFlavor,
+    mentions: Mentions,
+    bot_name: &'a str,
+    username: &'a str,
+    messages: &'a MessageTemplates,
+    time_format: TimeFormat,
+    timezone: Timezone,
+}
+
+impl Sender<'_> {
+    /// Renders a custom title template with the message's placeholder values.
+    fn title(
+        &self,
+        template: &str,
+        project: Option<&str>,
+        now: DateTime<Local>,
+        elapsed: usize,
+    ) -> String {
+        let now = self.timezone.convert(now);
+
+        template::render(
+            template,
+            &[
+                ("username", self.username),
+                ("date", &now.format("%m/%d/%Y").to_string()),
+                ("time", &now.format("%H:%M:%S").to_string()),
+                ("total", &self.time_format.format(elapsed)),
+                ("project", project.unwrap_or_default()),
+            ],
+        )
+    }
+}
+
+/// Prefixes `title` with `icon` and a space, unless the icon was configured away.
+fn with_icon(icon: &str, title: String) -> String {
+    if icon.is_empty() {
+        title
+    } else {
+        format!("{} {}", icon, title)
+    }
+}
+
+fn build_clock_in_payload(sender: &Sender, project: Option<&str>, now: DateTime<Local>) -> Value {
+    let title = match (&sender.messages.clock_in, project) {
+        (Some(template), _) => sender.title(template, project, now, 0),
+        (None, Some(project)) => format!("{} has clocked in on {}!", sender.username, project),
+        (None, None) => format!("{} has clocked in!", sender.username),
+    };
+    let title = with_icon(sender.messages.clock_in_icon(), title);
+    let now = sender.timezone.convert(now);
+    let date = now.format("%m/%d/%Y").to_string();
+    let time = now.format("%H:%M:%S (UTC%z)").to_string();
+    let description = format!("\nDate: {}\nTime: {}", date, time);
+
+    build_payload(sender, &title, &description, 0x00ff88)
+}
+
+fn build_clock_out_payload(
+    sender: &Sender,
+    project: Option<&str>,
+    session_note: Option<&str>,
+    now: DateTime<Local>,
+    worked: WorkedTime,
+    logs: &[LogEntry],
+    note: Option<&str>,
+) -> Value {
+    let title = match (&sender.messages.clock_out, project) {
+        (Some(template), _) => sender.title(template, project, now, worked.total),
+        (None, Some(project)) => format!("{} has clocked out of {}!", sender.username, project),
+        (None, None) => format!("{} has clocked out!", sender.username),
+    };
+    let title = with_icon(sender.messages.clock_out_icon(), title);
+    let description = clock_out_description(
+        now,
+        sender.timezone,
+        worked,
+        sender.time_format,
+        session_note,
+        logs,
+        note,
+    );
+
+    build_payload(sender, &title, &description, 0x00ff88)
+}
+
+fn build_checkpoint_payload(
+    sender: &Sender,
+    project: Option<&str>,
+    now: DateTime<Local>,
+    worked: WorkedTime,
+    logs: &[LogEntry],
+) -> Value {
+    let title = match project {
+        Some(project) => format!("{} reached a checkpoint on {}!", sender.username, project),
+        None => format!("{} reached a checkpoint!", sender.username),
+    };
+    let description = clock_out_description(
+        now,
+        sender.timezone,
+        worked,
+        sender.time_format,
+        None,
+        logs,
+        None,
+    );
+
+    build_payload(sender, &title, &description, 0xffd700)
+}
+
+/// Discord rejects embeds whose description is longer than this many characters.
+const DISCORD_DESCRIPTION_LIMIT: usize = 4096;
+
+/// Cuts `text` down to at most `limit` characters, dropping whole lines from the end and noting
+/// how many were left out. Text that already fits is returned unchanged.
+fn truncate_lines(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+
+    let lines = text.split('\n').collect::<Vec<&str>>();
+    let mut kept = 0;
+    let mut length = 0;
+
+    for line in &lines {
+        let omitted = format!("\n… {} more lines not shown", lines.len() - kept);
+        let added = line.chars().count() + usize::from(kept > 0);
+
+        if length + added + omitted.chars().count() > limit {
+            break;
+        }
+
+        kept += 1;
+        length += added;
+    }
+
+    format!(
+        "{}\n… {} more lines not shown",
+        lines[..kept].join("\n"),
+        lines.len() - kept
+    )
+}
+
+/// Breaks up the mentions in `text` that `mentions` doesn't allow, so usernames and logs can't
+/// ping a whole channel. Discord mentions get a zero-width space after the `@`, and Slack's
+/// `<!here>`-style commands and `<@user>` links have their `<` escaped.
+fn escape_mentions(text: &str, flavor: WebhookFlavor, mentions: Mentions) -> String {
+    let replacements: &[(&str, &str)] = match (flavor, mentions) {
+        (_, Mentions::All) => &[],
+        (WebhookFlavor::Discord, Mentions::Users) => &[
+            ("@everyone", "@\u{200b}everyone"),
+            ("@here", "@\u{200b}here"),
+            ("<@&", "<@\u{200b}&"),
+        ],
+        (WebhookFlavor::Discord, Mentions::Off) => &[
+            ("@everyone", "@\u{200b}everyone"),
+            ("@here", "@\u{200b}here"),
+            ("<@", "<@\u{200b}"),
+        ],
+        (WebhookFlavor::Slack, Mentions::Users) => &[("<!", "&lt;!")],
+        (WebhookFlavor::Slack, Mentions::Off) => &[("<!", "&lt;!"), ("<@", "&lt;@")],
+    };
+
+    replacements
+        .iter()
+        .fold(text.to_string(), |text, (from, to)| text.replace(from, to))
+}
+
+/// Shapes a titled, colored message into the JSON schema the sender's flavor expects.
+fn build_payload(sender: &Sender, title: &str, description: &str, color: u32) -> Value {
+    let title = escape_mentions(title, sender.flavor, sender.mentions);
+    let description = escape_mentions(description, sender.flavor, sender.mentions);
+
+    match sender.flavor {
+        WebhookFlavor::Discord => {
+            let mut payload = json!({
+                "username": sender.bot_name,
+                "embeds": [{
+                    "title": title,
+                    "description": truncate_lines(&description, DISCORD_DESCRIPTION_LIMIT),
+                    "color": color
+                }]
+            });
+
+            // Even a mention that slips past the escaping won't ping anyone it shouldn't.
+            match sender.mentions {
+                Mentions::Off => payload["allowed_mentions"] = json!({ "parse": [] }),
+                Mentions::Users => payload["allowed_mentions"] = json!({ "parse": ["users"] }),
+                Mentions::All => {}
+            }
+
+            payload
+        }
+        WebhookFlavor::Slack => json!({
+            "username": sender.bot_name,
+            "text": title,
+            "attachments": [{
+                "text": description.trim_start(),
+                "color": format!("#{:06x}", color)
+            }]
+        }),
+    }
+}
+
+const WEBHOOK_RETRIES: u32 = 3;
+const WEBHOOK_BACKOFF: Duration = Duration::from_millis(500);
+const WEBHOOK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One message waiting in the webhook queue.
+struct WebhookJob {
+    url: String,
+    payload: Value,
+    attachment: Option<Attachment>,
+}
+
+impl WebhookJob {
+    /// What a dry run records: the payload, with the attached file alongside it.
+    fn dry_run_payload(&self) -> Value {
+        let mut payload = self.payload.clone();
+
+        if let Some(attachment) = &self.attachment {
+            payload["attachment"] = json!({
+                "name": attachment.name,
+                "contents": attachment.contents
+            });
+        }
+
+        payload
+    }
+}
+
+/// A file uploaded with a message, such as the session export on clock out.
+#[derive(Clone)]
+struct Attachment {
+    name: String,
+    contents: String,
+}
+
+/// Starts the task that hands queued webhooks to one delivery task per destination URL. Each
+/// destination gets its messages one at a time, in the order they were sent, and a
+/// `Retry-After` holds back everything behind it there, so bursts of clock-ins and outs can't
+/// trip the rate limit or arrive out of order. A destination that's down doesn't hold up the
+/// others.
+fn spawn_webhook_worker(
+    client: Client,
+    webhook_status: Arc<Mutex<WebhookStatus>>,
+    dry_run: Option<DryRun>,
+    held_stderr: HeldStderr,
+) -> (UnboundedSender<WebhookJob>, JoinHandle<()>) {
+    let (queue, mut jobs) = mpsc::unbounded_channel::<WebhookJob>();
+
+    let worker = tokio::spawn(async move {
+        let mut destinations = HashMap::new();
+
+        while let Some(job) = jobs.recv().await {
+            let (destination, _) = destinations.entry(job.url.clone()).or_insert_with(|| {
+                spawn_destination(
+                    client.clone(),
+                    Arc::clone(&webhook_status),
+                    dry_run.clone(),
+                    held_stderr.clone(),
+                )
+            });
+            let _ = destination.send(job);
+        }
+
+        // Closing each destination's queue lets it finish what's left, then end.
+        for (destination, delivery) in destinations.into_values() {
+            drop(destination);
+            let _ = delivery.await;
+        }
+    });
+
+    (queue, worker)
+}
+
+/// The delivery task for a single destination, posting its jobs one after another.
+fn spawn_destination(
+    client: Client,
+    webhook_status: Arc<Mutex<WebhookStatus>>,
+    dry_run: Option<DryRun>,
+    held_stderr: HeldStderr,
+) -> (UnboundedSender<WebhookJob>, JoinHandle<()>) {
+    let (queue, mut jobs) = mpsc::unbounded_channel::<WebhookJob>();
+
+    let delivery = tokio::spawn(async move {
+        while let Some(job) = jobs.recv().await {
+            *webhook_status.lock().unwrap() = WebhookStatus::Sending;
+
+            let delivered = match &dry_run {
+                Some(dry_run) => match write_dry_run(dry_run, &held_stderr, &job.dry_run_payload())
+                {
+                    Ok(()) => true,
+                    Err(error) => {
+                        tracing::error!(%error, "dry run write failed");
+                        false
+                    }
+                },
+                None => post_webhook(&client, &job).await.is_ok(),
+            };
+            finish_webhook(&webhook_status, delivered);
+        }
+    });
+
+    (queue, delivery)
+}
+
+/// Posts the job's payload, retrying network errors, 5xx and 429 responses with exponential
+/// backoff. A 429's `Retry-After` header takes precedence over the backoff delay.
+async fn post_webhook(client: &Client, job: &WebhookJob) -> reqwest::Result<()> {
+    let host = webhook_host(&job.url);
+    let mut backoff = WEBHOOK_BACKOFF;
+    let mut retries = 0;
+
+    loop {
+        let request = match &job.attachment {
+            Some(attachment) => client
+                .post(&job.url)
+                .multipart(multipart_form(&job.payload, attachment)),
+            None => client.post(&job.url).json(&job.payload),
+        };
+        let result = request.send().await;
+
+        let delay = match &result {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                Some(retry_after(response).unwrap_or(backoff))
+            }
+            Ok(response) if response.status().is_server_error() => Some(backoff),
+            Err(error) if !error.is_builder() => Some(backoff),
+            _ => None,
+        };
+
+        let Some(delay) = delay.filter(|_| retries < WEBHOOK_RETRIES) else {
+            let delivered = result.and_then(Response::error_for_status);
+
+            match &delivered {
+                Ok(response) => {
+                    tracing::info!(host, status = %response.status(), "webhook delivered")
+                }
+                Err(error) => {
+                    tracing::error!(host, error = redact_url(error), "webhook failed")
+                }
+            }
+
+            return delivered.map(|_| ());
+        };
+
+        match &result {
+            Ok(response) => {
+                tracing::warn!(host, status = %response.status(), ?delay, retries, "webhook retrying")
+            }
+            Err(error) => {
+                tracing::warn!(
+                    host,
+                    error = redact_url(error),
+                    ?delay,
+                    retries,
+                    "webhook retrying"
+                )
+            }
+        }
+
+        tokio::time::sleep(delay).await;
+        retries += 1;
+        backoff *= 2;
+    }
+}
+
+/// The webhook's host for the log, leaving out the path with its secret token.
+fn webhook_host(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// The error's message with the request URL cut down to its host, so no token ends up in the log.
+fn redact_url(error: &reqwest::Error) -> String {
+    match error.url() {
+        Some(url) => error
+            .to_string()
+            .replace(url.as_str(), url.host_str().unwrap_or_default()),
+        None => error.to_string(),
+    }
+}
+
+/// Discord's file upload: the message goes in `payload_json`, next to the file itself.
+fn multipart_form(payload: &Value, attachment: &Attachment) -> Form {
+    Form::new().text("payload_json", payload.to_string()).part(
+        "files[0]",
+        Part::bytes(attachment.contents.clone().into_bytes()).file_name(attachment.name.clone()),
+    )
+}
+
+fn warn_storage(error: &StorageError) {
+    eprintln!(
+        "WorkWatch Warning: {}! Continuing without it, changes may not be saved.",
+        error
+    );
+}
+
+/// Records `payload` as one JSON line instead of posting it.
+fn write_dry_run(dry_run: &DryRun, held_stderr: &HeldStderr, payload: &Value) -> io::Result<()> {
+    let line = format!("{}\n", payload);
+
+    match dry_run {
+        DryRun::Stderr => held_stderr.write(&line),
+        DryRun::File(path) => Ok(storage::append(path, &line)?),
+    }
+}
+
+fn finish_webhook(webhook_status: &Mutex<WebhookStatus>, delivered: bool) {
+    *webhook_status.lock().unwrap() = if delivered {
+        WebhookStatus::Sent(Instant::now())
+    } else {
+        WebhookStatus::Failed(Instant::now())
+    };
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse::<f64>()
+        .ok()?;
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let mut cli = Cli::parse();
+
+    dotenv().ok();
+
+    if let Some(format) = cli.export {
+        let records = History::new(data_dir().join("history.jsonl")).load()?;
+        print!("{}", export::export(format, &records)?);
+        return Ok(());
+    }
+
+    let command = cli.command.take();
+    let json_summary = cli.json_summary;
+    let config = Config::load(cli);
+    logging::init(&config.data_dir, config.log_level.as_deref());
+    let start_working = config.start_working;
+    let mut app = WorkWatcherApp::new(config);
+    let result = match command {
+        Some(command) => {
+            if let Err(error) = app.run_command(command) {
+                eprintln!("WorkWatch Error: {}!", error);
+                process::exit(1);
+            }
+
+            Ok(())
+        }
+        None => {
+            if start_working {
+                app.start_working();
+            }
+
+            app.run()
+        }
+    };
+    app.finish_webhooks().await;
+
+    // `run` has restored the terminal by now, so this lands on the real stdout.
+    if json_summary && let Some(summary) = &app.ended_session {
+        println!("{}", summary);
+    }
+
+    if let Some(error) = &app.storage_error {
+        eprintln!(
+            "WorkWatch Warning: {}! Some changes were only kept in memory.",
+            error
+        );
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, path::Path};
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::keymap::KeyNames;
+
+    fn worked(total: usize) -> WorkedTime {
+        WorkedTime { total, breaks: 0 }
+    }
+
+    fn parse_compact(compact: &str) -> usize {
+        compact
+            .split(':')
+            .rev()
+            .zip([1, 60, 3_600, 86_400])
+            .map(|(part, unit)| part.parse::<usize>().unwrap() * unit)
+            .sum()
+    }
+
+    fn parse_verbose(verbose: &str) -> usize {
+        verbose
+            .split(", ")
+            .map(|part| {
+                let (amount, unit) = part.split_once(' ').unwrap();
+                let unit = match unit {
+                    "Days" => 86_400,
+                    "Hours" => 3_600,
+                    "Minutes" => 60,
+                    "Seconds" => 1,
+                    _ => panic!("unexpected unit {:?}", unit),
+                };
+
+                amount.parse::<usize>().unwrap() * unit
+            })
+            .sum()
+    }
+
+    #[test]
+    fn time_representations_agree() {
+        for elapsed in [
+            0, 1, 59, 60, 61, 3_599, 3_600, 3_661, 86_399, 86_400, 90_061, 400_000,
+        ] {
+            let verbose = format_verbose(elapsed);
+            let description = clock_out_description(
+                Local::now(),
+                Timezone::Local,
+                worked(elapsed),
+                TimeFormat::Verbose,
+                None,
+                &[],
+                None,
+            );
+
+            assert_eq!(parse_compact(&format_compact(elapsed)), elapsed);
+            assert_eq!(parse_verbose(&verbose), elapsed);
+            assert!(description.contains(&format!("Total Logged Time: {}\n", verbose)));
+        }
+    }
+
+    static NO_MESSAGES: MessageTemplates = MessageTemplates {
+        clock_in: None,
+        clock_out: None,
+        clock_in_icon: None,
+        clock_out_icon: None,
+    };
+
+    fn sender(flavor: WebhookFlavor) -> Sender<'static> {
+        Sender {
+            flavor,
+            mentions: Mentions::Off,
+            bot_name: "WorkWatch",
+            username: "Ada",
+            messages: &NO_MESSAGES,
+            time_format: TimeFormat::Verbose,
+            timezone: Timezone::Local,
+        }
+    }
+
+    fn noon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 8, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn clock_in_payload_names_the_user_and_project() {
+        let payload = build_clock_in_payload(&sender(WebhookFlavor::Discord), None, noon());
+        let embed = &payload["embeds"][0];
+
+        assert_eq!(payload["username"], "WorkWatch");
+        assert_eq!(embed["title"], "🟢 Ada has clocked in!");
+        assert_eq!(
+            embed["description"],
+            format!(
+                "\nDate: 03/08/2024\nTime: {}",
+                noon().format("%H:%M:%S (UTC%z)")
+            )
+        );
+        assert_eq!(embed["color"], 0x00ff88);
+
+        let payload =
+            build_clock_in_payload(&sender(WebhookFlavor::Discord), Some("Website"), noon());
+
+        assert_eq!(
+            payload["embeds"][0]["title"],
+            "🟢 Ada has clocked in on Website!"
+        );
+    }
+
+    #[test]
+    fn clock_out_payload_without_logs() {
+        let payload = build_clock_out_payload(
+            &sender(WebhookFlavor::Discord),
+            None,
+            None,
+            noon(),
+            worked(90),
+            &[],
+            None,
+        );
+        let embed = &payload["embeds"][0];
+        let description = embed["description"].as_str().unwrap();
+
+        assert_eq!(embed["title"], "🔴 Ada has clocked out!");
+        assert_eq!(embed["color"], 0x00ff88);
+        assert!(description.contains("Total Logged Time: 1 Minutes, 30 Seconds\n"));
+        assert!(description.ends_with("No logs to display."));
+    }
+
+    #[test]
+    fn clock_out_payload_with_logs() {
+        let logs = [
+            LogEntry::parse("#design header", Duration::from_secs(5)),
+            LogEntry::parse("footer", Duration::from_secs(3_725)),
+        ];
+        let payload = build_clock_out_payload(
+            &sender(WebhookFlavor::Discord),
+            Some("Website"),
+            Some("Finish the landing page"),
+            noon(),
+            worked(4_000),
+            &logs,
+            Some("Automatically clocked out."),
+        );
+        let embed = &payload["embeds"][0];
+        let description = embed["description"].as_str().unwrap();
+
+        assert_eq!(embed["title"], "🔴 Ada has clocked out of Website!");
+        assert!(description.starts_with("\nSession Note: Finish the landing page\n\nDate: "));
+        assert!(description.contains("\nNote: Automatically clocked out.\n"));
+        assert!(description.contains(
+            "Logs:\n[00:00:05] #design header\n[01:02:05] footer\n\nCategories: #design (1)"
+        ));
+        assert!(!description.contains("No logs to display."));
+    }
+
+    #[test]
+    fn break_logs_record_their_length() {
+        let logs = [
+            LogEntry::parse("#break 45m lunch", Duration::from_secs(7_200)),
+            LogEntry::parse("#break 1h30m", Duration::from_secs(14_400)),
+            LogEntry::parse("#break coffee", Duration::from_secs(18_000)),
+            LogEntry::parse("45m of reviews", Duration::from_secs(20_000)),
+        ];
+
+        assert_eq!(logs[0].break_length, Some(Duration::from_secs(2_700)));
+        assert_eq!(logs[0].input(), "#break 45m lunch");
+        assert_eq!(logs[2].break_length, None);
+        assert_eq!(logs[3].break_length, None);
+        assert_eq!(break_secs(&logs), 8_100);
+
+        let description = clock_out_description(
+            noon(),
+            Timezone::Local,
+            WorkedTime {
+                total: 18_000,
+                breaks: break_secs(&logs),
+            },
+            TimeFormat::Short,
+            None,
+            &logs,
+            None,
+        );
+
+        assert!(description.contains("Total Logged Time: 5h 0m\nBreaks: 2h 15m\n\nLogs:\n"));
+    }
+
+    #[tokio::test]
+    async fn breaks_cleared_by_a_checkpoint_are_still_reported() {
+        let dir = scratch_dir("checkpoint-breaks");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        app.base_seconds = 7_200;
+        add_log(&mut app, "#break 30m lunch");
+        press(&mut app, KeyCode::Char('C'));
+        add_log(&mut app, "after lunch");
+
+        let payload = build_clock_out_payload(
+            &sender(WebhookFlavor::Discord),
+            None,
+            None,
+            noon(),
+            app.worked_time(),
+            &app.logs,
+            None,
+        );
+        let description = payload["embeds"][0]["description"].as_str().unwrap();
+        assert!(description.contains(
+            "Total Logged Time: 1 Hours, 30 Minutes, 0 Seconds\nBreaks: 30 Minutes, 0 Seconds\n"
+        ));
+
+        press(&mut app, KeyCode::Char('c'));
+        let record = &app.history.load().unwrap()[0];
+        assert!((5_400..5_410).contains(&record.seconds));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn logs_with_a_creation_time_show_both_stamps() {
+        let log = LogEntry {
+            created_at: Some(noon()),
+            ..LogEntry::parse("#design header", Duration::from_secs(65))
+        };
+
+        assert_eq!(log.clock_timestamp(), "[12:00:00]");
+        assert_eq!(
+            log.stamped(Timezone::Local),
+            "[00:01:05, 12:00:00] #design header"
+        );
+    }
+
+    #[test]
+    fn banner_lines_share_one_width() {
+        let lines = banner_lines(" /\\\n/__\\ ok\n");
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].to_string(), " /\\    ");
+        assert_eq!(lines[1].to_string(), "/__\\ ok");
+        assert_eq!(lines[2].width(), 0);
+    }
+
+    #[test]
+    fn scheduled_clock_out_only_ends_sessions_started_before_it() {
+        let six = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        let at = |hour| Local.with_ymd_and_hms(2024, 3, 8, hour, 0, 0).unwrap();
+
+        assert!(!scheduled_clock_out_due(at(9), at(17), six));
+        assert!(scheduled_clock_out_due(at(9), at(18), six));
+        assert!(scheduled_clock_out_due(at(9), at(21), six));
+        assert!(!scheduled_clock_out_due(at(19), at(21), six));
+        assert!(scheduled_clock_out_due(
+            at(19),
+            at(18) + chrono::Duration::days(1),
+            six
+        ));
+    }
+
+    #[test]
+    fn sessions_past_midnight_are_split_by_day() {
+        let midnight = Local.with_ymd_and_hms(2024, 3, 9, 0, 0, 0).unwrap();
+        let log = |text, at| LogEntry {
+            created_at: Some(at),
+            ..LogEntry::parse(text, Duration::ZERO)
+        };
+        let logs = [
+            log("late fix", noon() + chrono::Duration::hours(11)),
+            log("deploy", midnight + chrono::Duration::hours(1)),
+        ];
+        let session = SessionRecord {
+            clocked_in_at: noon() + chrono::Duration::hours(10),
+            clocked_out_at: midnight + chrono::Duration::hours(2),
+            seconds: 14_400,
+            project: Some("Client A".to_string()),
+            logs: vec![],
+            note: Some("release night".to_string()),
+        };
+        let splits = [DaySplit {
+            at: midnight,
+            seconds: 7_000,
+        }];
+
+        let records = split_by_day(session, &logs, &splits);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].clocked_out_at, midnight);
+        assert_eq!((records[0].seconds, records[1].seconds), (7_000, 7_400));
+        assert_eq!(records[0].logs, ["late fix"]);
+        assert_eq!(records[1].logs, ["deploy"]);
+        assert_eq!(records[0].note, None);
+        assert_eq!(records[1].note.as_deref(), Some("release night"));
+        assert_eq!(records[1].project.as_deref(), Some("Client A"));
+    }
+
+    #[test]
+    fn json_summary_lists_the_whole_session() {
+        let session = SessionRecord {
+            clocked_in_at: noon(),
+            clocked_out_at: noon() + chrono::Duration::hours(2),
+            seconds: 7_200,
+            project: None,
+            logs: vec![],
+            note: None,
+        };
+        let logs = [LogEntry::parse(
+            "#review read the PR",
+            Duration::from_secs(90),
+        )];
+
+        let summary = json_summary(&session, &logs);
+
+        assert_eq!(summary["clock_in"], noon().to_rfc3339());
+        assert_eq!(summary["total_seconds"], 7_200);
+        assert_eq!(summary["log_count"], 1);
+        assert_eq!(summary["logs"][0]["at"], 90);
+        assert_eq!(summary["logs"][0]["text"], "#review read the PR");
+        assert!(summary["project"].is_null());
+    }
+
+    #[test]
+    fn webhook_times_follow_the_configured_timezone() {
+        let at = chrono::Utc
+            .with_ymd_and_hms(2024, 3, 8, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let tokyo = Timezone::try_from("Asia/Tokyo".to_string()).ok().unwrap();
+
+        assert_eq!(
+            Timezone::Utc.convert(at).format("%H:%M %z").to_string(),
+            "12:00 +0000"
+        );
+        assert_eq!(
+            tokyo.convert(at).format("%H:%M %z").to_string(),
+            "21:00 +0900"
+        );
+        assert!(Timezone::try_from("Mars/Olympus".to_string()).is_err());
+    }
+
+    #[test]
+    fn selection_steps_wrap_or_stop_at_the_ends() {
+        assert_eq!(step_position(0, 3, -1, true), Some(2));
+        assert_eq!(step_position(2, 3, 1, true), Some(0));
+        assert_eq!(step_position(1, 3, 1, true), Some(2));
+        assert_eq!(step_position(1, 3, -10, false), Some(0));
+        assert_eq!(step_position(1, 3, 10, false), Some(2));
+        assert_eq!(step_position(5, 3, 0, false), Some(2));
+        assert_eq!(step_position(0, 0, 1, true), None);
+        assert_eq!(step_position(0, 0, -10, false), None);
+    }
+
+    #[test]
+    fn prompt_cursor_follows_line_breaks() {
+        assert_eq!(cursor_row_column(&Input::default()), (0, 0));
+        assert_eq!(cursor_row_column(&Input::new("fix bug".into())), (0, 7));
+        assert_eq!(
+            cursor_row_column(&Input::new("fix bug".into()).with_cursor(3)),
+            (0, 3)
+        );
+        assert_eq!(
+            cursor_row_column(&Input::new("first\nsecond".into())),
+            (1, 6)
+        );
+        assert_eq!(cursor_row_column(&Input::new("日本".into())), (0, 4));
+    }
+
+    #[test]
+    fn consecutive_duplicate_logs_are_removed() {
+        let mut logs = ["a", "a", "#x b", "#x b", "#x b", "a", "#y b"]
+            .iter()
+            .enumerate()
+            .map(|(index, input)| LogEntry::parse(input, Duration::from_secs(index as u64)))
+            .collect::<Vec<LogEntry>>();
+
+        let removed = remove_consecutive_duplicates(&mut logs);
+
+        assert_eq!(
+            logs.iter()
+                .map(|log| log.stamped(Timezone::Local))
+                .collect::<Vec<String>>(),
+            [
+                "[00:00:00] a",
+                "[00:00:02] #x b",
+                "[00:00:05] a",
+                "[00:00:06] #y b"
+            ]
+        );
+        assert_eq!(
+            removed
+                .iter()
+                .map(|(index, _)| *index)
+                .collect::<Vec<usize>>(),
+            [1, 3, 4]
+        );
+    }
+
+    #[test]
+    fn multiline_logs_stay_inside_their_entry() {
+        let log = LogEntry::parse(
+            "#review first pass\nleft comments\n",
+            Duration::from_secs(65),
+        );
+
+        assert_eq!(log.tags, ["review"]);
+        assert_eq!(log.text, "first pass\nleft comments");
+        assert_eq!(
+            log.stamped(Timezone::Local),
+            "[00:01:05] #review first pass\n  left comments"
+        );
+    }
+
+    #[test]
+    fn checkpoint_payload_lists_the_logs_so_far() {
+        let logs = [LogEntry::parse("#design header", Duration::from_secs(5))];
+        let payload = build_checkpoint_payload(
+            &sender(WebhookFlavor::Discord),
+            Some("Website"),
+            noon(),
+            worked(600),
+            &logs,
+        );
+        let embed = &payload["embeds"][0];
+
+        assert_eq!(embed["title"], "Ada reached a checkpoint on Website!");
+        assert!(embed["description"].as_str().unwrap().contains(
+            "Total Logged Time: 10 Minutes, 0 Seconds\n\nLogs:\n[00:00:05] #design header"
+        ));
+    }
+
+    #[test]
+    fn webhook_logs_leave_out_the_token() {
+        assert_eq!(
+            webhook_host("https://discord.com/api/webhooks/123/secret-token"),
+            "discord.com"
+        );
+        assert_eq!(webhook_host(""), "");
+    }
+
+    #[test]
+    fn oversized_clock_out_payload_is_truncated() {
+        let logs = (0..200)
+            .map(|index| {
+                LogEntry::parse(
+                    &format!(
+                        "#chore worked through a long list of small tasks, part {}",
+                        index
+                    ),
+                    Duration::from_secs(index * 60),
+                )
+            })
+            .collect::<Vec<LogEntry>>();
+        let payload = build_clock_out_payload(
+            &sender(WebhookFlavor::Discord),
+            None,
+            None,
+            noon(),
+            worked(12_000),
+            &logs,
+            None,
+        );
+        let description = payload["embeds"][0]["description"].as_str().unwrap();
+        let full = clock_out_description(
+            noon(),
+            Timezone::Local,
+            worked(12_000),
+            TimeFormat::Verbose,
+            None,
+            &logs,
+            None,
+        );
+
+        assert!(full.chars().count() > DISCORD_DESCRIPTION_LIMIT);
+        assert!(description.chars().count() <= DISCORD_DESCRIPTION_LIMIT);
+        assert!(description.contains("Logs:\n[00:00:00] #chore worked through"));
+        assert!(description.ends_with("more lines not shown"));
+
+        let (shown, omitted) = description.rsplit_once("\n… ").unwrap();
+        let omitted = omitted.split(' ').next().unwrap().parse::<usize>().unwrap();
+
+        assert!(full.starts_with(shown));
+        assert_eq!(
+            shown.split('\n').count() + omitted,
+            full.split('\n').count()
+        );
+    }
+
+    #[test]
+    fn custom_titles_fill_in_placeholders() {
+        let messages = MessageTemplates {
+            clock_in: Some("{username} started {project} at {time}".to_string()),
+            clock_out: Some("{username} wrapped up after {total} on {date}".to_string()),
+            clock_in_icon: Some(String::new()),
+            clock_out_icon: Some("🏁".to_string()),
+        };
+        let sender = Sender {
+            messages: &messages,
+            ..sender(WebhookFlavor::Discord)
+        };
+
+        let payload = build_clock_in_payload(&sender, Some("Website"), noon());
+        assert_eq!(
+            payload["embeds"][0]["title"],
+            "Ada started Website at 12:00:00"
+        );
+
+        let payload =
+            build_clock_out_payload(&sender, None, None, noon(), worked(5_400), &[], None);
+        assert_eq!(
+            payload["embeds"][0]["title"],
+            "🏁 Ada wrapped up after 1 Hours, 30 Minutes, 0 Seconds on 03/08/2024"
+        );
+    }
+
+    #[test]
+    fn mentions_are_escaped_by_default() {
+        let sender = Sender {
+            username: "@everyone",
+            ..sender(WebhookFlavor::Discord)
+        };
+        let logs = [LogEntry::parse(
+            "paged <@123> and <@&456> @here",
+            Duration::from_secs(5),
+        )];
+        let payload = build_clock_out_payload(&sender, None, None, noon(), worked(60), &logs, None);
+        let embed = &payload["embeds"][0];
+
+        assert_eq!(embed["title"], "🔴 @\u{200b}everyone has clocked out!");
+        assert!(
+            embed["description"]
+                .as_str()
+                .unwrap()
+                .contains("paged <@\u{200b}123> and <@\u{200b}&456> @\u{200b}here")
+        );
+        assert_eq!(payload["allowed_mentions"], json!({ "parse": [] }));
+    }
+
+    #[test]
+    fn mention_strictness_is_configurable() {
+        let text = "@here <@123> <@&456>";
+
+        assert_eq!(
+            escape_mentions(text, WebhookFlavor::Discord, Mentions::Users),
+            "@\u{200b}here <@123> <@\u{200b}&456>"
+        );
+        assert_eq!(
+            escape_mentions(text, WebhookFlavor::Discord, Mentions::All),
+            text
+        );
+        assert_eq!(
+            escape_mentions("<!here> <@U123>", WebhookFlavor::Slack, Mentions::Off),
+            "&lt;!here> &lt;@U123>"
+        );
+        assert_eq!(
+            escape_mentions("<!here> <@U123>", WebhookFlavor::Slack, Mentions::Users),
+            "&lt;!here> <@U123>"
+        );
+
+        let sender = Sender {
+            mentions: Mentions::All,
+            ..sender(WebhookFlavor::Discord)
+        };
+
+        assert!(build_clock_in_payload(&sender, None, noon())["allowed_mentions"].is_null());
+    }
+
+    #[test]
+    fn title_icons_survive_json_encoding() {
+        let payload = build_clock_out_payload(
+            &sender(WebhookFlavor::Discord),
+            None,
+            None,
+            noon(),
+            worked(60),
+            &[],
+            None,
+        );
+        let encoded = serde_json::to_string(&payload).unwrap();
+
+        assert!(encoded.contains("\"title\":\"🔴 Ada has clocked out!\""));
+        assert_eq!(serde_json::from_str::<Value>(&encoded).unwrap(), payload);
+    }
+
+    #[test]
+    fn slack_payload_uses_attachments() {
+        let payload = build_clock_in_payload(&sender(WebhookFlavor::Slack), None, noon());
+
+        assert_eq!(payload["text"], "🟢 Ada has clocked in!");
+        assert_eq!(payload["attachments"][0]["color"], "#00ff88");
+        assert!(
+            payload["attachments"][0]["text"]
+                .as_str()
+                .unwrap()
+                .starts_with("Date: 03/08/2024")
+        );
+    }
+
+    /// A scratch directory for one test's session, history and exports.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("workwatch-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// An app with no webhook and no config file, storing everything under `dir`.
+    fn test_app(dir: &Path) -> WorkWatcherApp {
+        let cli = Cli::parse_from(["workwatch", "--username", "Ada", "--webhook", ""]);
+
+        WorkWatcherApp::new(Config {
+            data_dir: dir.to_path_buf(),
+            export_dir: dir.to_path_buf(),
+            dry_run: None,
+            status_file: None,
+            status_line_file: None,
+            history_retention_days: None,
+            ..Config::without_file(cli)
+        })
+    }
+
+    fn press(app: &mut WorkWatcherApp, code: KeyCode) {
+        let _ = app.handle_key(KeyEvent::from(code));
+    }
+
+    fn type_text(app: &mut WorkWatcherApp, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    fn add_log(app: &mut WorkWatcherApp, text: &str) {
+        press(app, KeyCode::Char('a'));
+        type_text(app, text);
+        press(app, KeyCode::Enter);
+    }
+
+    fn log_texts(app: &WorkWatcherApp) -> Vec<String> {
+        app.logs.iter().map(LogEntry::input).collect()
+    }
+
+    #[tokio::test]
+    async fn clocking_in_and_logging_through_keys() {
+        let dir = scratch_dir("keys-clock-in");
+        let mut app = test_app(&dir);
+
+        assert!(matches!(app.state, AppState::Menu));
+
+        press(&mut app, KeyCode::Char('c'));
+        assert!(matches!(app.state, AppState::Working));
+
+        press(&mut app, KeyCode::Char('a'));
+        assert!(app.prompt_state == PromptState::Input);
+
+        type_text(&mut app, "#docs write the readme");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.prompt_state == PromptState::NoPrompt);
+        assert_eq!(log_texts(&app), ["#docs write the readme"]);
+
+        // Blank input and Esc both close the prompt without adding anything.
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "never mind");
+        press(&mut app, KeyCode::Esc);
+        assert!(app.prompt_state == PromptState::NoPrompt);
+        assert_eq!(app.logs.len(), 1);
+
+        press(&mut app, KeyCode::Char('l'));
+        assert!(matches!(app.state, AppState::Logs));
+        assert_eq!(app.selected_log, Some(0));
+
+        press(&mut app, KeyCode::Char('t'));
+        assert!(matches!(app.state, AppState::Working));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn editing_deleting_and_undoing_logs() {
+        let dir = scratch_dir("keys-edit");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "first");
+        add_log(&mut app, "second");
+        press(&mut app, KeyCode::Char('l'));
+
+        press(&mut app, KeyCode::Char('e'));
+        assert!(app.prompt_state == PromptState::Edit);
+        assert_eq!(app.prompt_input.value(), "first");
+        type_text(&mut app, " draft");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(log_texts(&app), ["first draft", "second"]);
+
+        press(&mut app, KeyCode::Char('A'));
+        assert_eq!(app.prompt_input.value(), "first draft ");
+        press(&mut app, KeyCode::Esc);
+
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.prompt_state == PromptState::Confirm);
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.logs.len(), 2);
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(log_texts(&app), ["second"]);
+        assert_eq!(app.selected_log, Some(0));
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(log_texts(&app), ["first draft", "second"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn digits_bound_to_actions_are_not_log_numbers() {
+        let dir = scratch_dir("keys-digit-binding");
+        let mut app = test_app(&dir);
+        app.keymap =
+            Keymap::with_overrides(HashMap::from([(Action::First, KeyNames::One("0".into()))]));
+
+        press(&mut app, KeyCode::Char('c'));
+        for text in ["one", "two", "three"] {
+            add_log(&mut app, text);
+        }
+        press(&mut app, KeyCode::Char('l'));
+
+        press(&mut app, KeyCode::Char('0'));
+        assert_eq!(app.selected_log, Some(0));
+        assert!(app.log_jump.is_empty());
+
+        press(&mut app, KeyCode::Char('2'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.selected_log, Some(1));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn log_navigation_wraps_and_survives_an_empty_filter() {
+        let dir = scratch_dir("keys-navigation");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        for text in ["one", "two", "three"] {
+            add_log(&mut app, text);
+        }
+        press(&mut app, KeyCode::Char('l'));
+
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.selected_log, Some(2));
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.selected_log, Some(0));
+        // The page size is normally measured while drawing.
+        app.log_page = 10;
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.selected_log, Some(2));
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.selected_log, Some(0));
+        press(&mut app, KeyCode::End);
+        assert_eq!(app.selected_log, Some(2));
+
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "nothing matches");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.selected_log, None);
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::PageUp);
+        assert_eq!(app.selected_log, None);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.selected_log, Some(0));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn clocking_out_shows_the_summary_then_the_menu() {
+        let dir = scratch_dir("keys-clock-out");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "shipped it");
+        press(&mut app, KeyCode::Char('c'));
+
+        assert!(matches!(app.state, AppState::Summary));
+        assert_eq!(
+            app.session_summary.as_ref().map(|summary| summary.logs),
+            Some(1)
+        );
+        assert_eq!(app.history.load().unwrap().len(), 1);
+
+        press(&mut app, KeyCode::Char('x'));
+        assert!(matches!(app.state, AppState::Menu));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn manual_adjustments_stop_at_zero_and_are_noted() {
+        let dir = scratch_dir("adjust-time");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        press(&mut app, KeyCode::Char('+'));
+        type_text(&mut app, "+10m");
+        press(&mut app, KeyCode::Enter);
+        assert!((600..610).contains(&app.elapsed_secs()));
+
+        press(&mut app, KeyCode::Char('+'));
+        type_text(&mut app, "+999999999999999999h");
+        press(&mut app, KeyCode::Enter);
+        assert!((600..610).contains(&app.elapsed_secs()));
+
+        press(&mut app, KeyCode::Char('+'));
+        type_text(&mut app, "-20m");
+        press(&mut app, KeyCode::Enter);
+        assert!(app.elapsed_secs() < 10);
+        assert_eq!(app.adjustment_note(), None);
+
+        press(&mut app, KeyCode::Char('+'));
+        type_text(&mut app, "5");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(
+            app.adjustment_note().as_deref(),
+            Some("Includes a +5m manual adjustment.")
+        );
+
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(
+            app.history.load().unwrap()[0].note.as_deref(),
+            Some("Includes a +5m manual adjustment.")
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn sessions_saved_from_the_logs_view_are_restored() {
+        let dir = scratch_dir("restore-from-logs");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "before the crash");
+        press(&mut app, KeyCode::Char('l'));
+        app.base_seconds = 1_800;
+        app.save_session().unwrap();
+
+        let restored = test_app(&dir);
+        assert!(restored.clocked_in());
+        assert_eq!(restored.restored_seconds, Some(1_800));
+        assert_eq!(log_texts(&restored), ["before the crash"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn each_session_records_only_its_own_logs() {
+        let dir = scratch_dir("two-sessions");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "first");
+        press(&mut app, KeyCode::Char('c'));
+        assert!(app.logs.is_empty());
+
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "second");
+        press(&mut app, KeyCode::Char('c'));
+
+        let history = app.history.load().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].logs, ["first"]);
+        assert_eq!(history[1].logs, ["second"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn word_goal_counts_log_words_without_tags() {
+        let dir = scratch_dir("word-goal");
+        let mut app = test_app(&dir);
+        app.word_goal = Some(1_000);
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "#draft opened chapter two");
+        add_log(&mut app, "cut the prologue");
+        assert_eq!(app.word_progress().as_deref(), Some("6 / 1000 words"));
+
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(
+            app.history.load().unwrap()[0].note.as_deref(),
+            Some("Wrote 6 / 1000 words.")
+        );
+
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(app.word_progress().as_deref(), Some("0 / 1000 words"));
+        add_log(&mut app, "a fresh page");
+        press(&mut app, KeyCode::Char('c'));
+        assert_eq!(
+            app.history.load().unwrap()[1].note.as_deref(),
+            Some("Wrote 3 / 1000 words.")
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn working_view_previews_the_latest_logs() {
+        let dir = scratch_dir("recent-logs");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        for text in ["one", "two", "three", "four"] {
+            add_log(&mut app, text);
+        }
+        press(&mut app, KeyCode::Char('t'));
+
+        let lines = app
+            .working_lines()
+            .iter()
+            .map(Line::to_string)
+            .collect::<Vec<String>>();
+        let preview = &lines[lines.len() - 3..];
+
+        assert!(preview[0].ends_with(" two"));
+        assert!(preview[2].ends_with(" four"));
+        assert!(!lines.iter().any(|line| line.ends_with(" one")));
+
+        app.presenting = true;
+        assert!(
+            !app.working_lines()
+                .iter()
+                .any(|line| line.to_string().ends_with(" four"))
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn every_listed_webhook_gets_its_own_flavor() {
+        let dir = scratch_dir("webhook-routes");
+        let mut app = test_app(&dir);
+
+        app.webhook_url =
+            "https://discord.com/api/webhooks/1/a, https://hooks.slack.com/services/b,".to_string();
+
+        let routes = app.webhook_routes();
+
+        assert_eq!(routes.len(), 2);
+        assert!(routes[0].0.ends_with("/a") && routes[0].1 == WebhookFlavor::Discord);
+        assert!(routes[1].0.ends_with("/b") && routes[1].1 == WebhookFlavor::Slack);
+
+        app.project_webhooks.insert(
+            "Website".to_string(),
+            "https://example.com/hook".to_string(),
+        );
+        app.project = Some("Website".to_string());
+        assert_eq!(app.webhook_routes().len(), 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn solo_mode_says_checkpoints_are_not_posted() {
+        let dir = scratch_dir("solo");
+        let mut app = test_app(&dir);
+
+        assert!(!app.webhooks_enabled());
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "offline work");
+        app.checkpoint();
+
+        assert_eq!(
+            app.notice().as_deref(),
+            Some(" Checkpoint: 1 log archived (not posted) ")
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn long_sessions_attach_their_export() {
+        let dir = scratch_dir("attach-export");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "short day");
+        assert!(app.clock_out_attachment(None).is_none());
+
+        for index in 0..100 {
+            app.logs.push(LogEntry::parse(
+                &format!("reviewed pull request {} and left a few comments", index),
+                Duration::ZERO,
+            ));
+        }
+
+        let attachment = app.clock_out_attachment(None).unwrap();
+
+        assert!(attachment.name.starts_with("session-"));
+        assert!(attachment.contents.contains("- Total Logged Time: "));
+        assert!(attachment.contents.contains("reviewed pull request 99"));
+
+        app.attach_export = AttachExport::Never;
+        assert!(app.clock_out_attachment(None).is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn clocking_out_can_ask_first() {
+        let dir = scratch_dir("keys-confirm-clock-out");
+        let mut app = test_app(&dir);
+        app.clock_out_confirmation = true;
+
+        app.clock_in();
+        app.logs
+            .push(LogEntry::parse("wrote the preview", Duration::ZERO));
+
+        press(&mut app, KeyCode::Char('c'));
+        assert!(app.confirm_clock_out);
+        assert_eq!(app.clock_out_preview(), "1 log, 0s will be saved");
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.confirm_clock_out);
+        assert!(app.started_at.is_some());
+
+        press(&mut app, KeyCode::Char('c'));
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.confirm_clock_out);
+        assert!(app.started_at.is_none());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn ctrl_c_clocks_out_and_quits() {
+        let dir = scratch_dir("keys-ctrl-c");
+        let mut app = test_app(&dir);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+
+        press(&mut app, KeyCode::Char('c'));
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "half typed");
+
+        assert!(app.handle_key(ctrl_c).is_break());
+        assert!(!app.clocked_in());
+        assert_eq!(app.history.load().unwrap().len(), 1);
+        assert!(dir.join("session.json").exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn quitting_asks_first_while_logs_are_listed() {
+        let dir = scratch_dir("keys-quit");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('?'));
+        assert!(app.show_help);
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.show_help);
+
+        app.logs.push(LogEntry::parse("left over", Duration::ZERO));
+
+        let quit = KeyEvent::from(KeyCode::Char('q'));
+
+        assert!(app.handle_key(quit).is_continue());
+        assert!(app.confirm_quit);
+        press(&mut app, KeyCode::Char('c'));
+        assert!(!app.confirm_quit);
+
+        assert!(app.handle_key(quit).is_continue());
+        assert!(
+            app.handle_key(KeyEvent::from(KeyCode::Char('s')))
+                .is_break()
+        );
+        assert!(dir.join("session.json").exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn quitting_after_clocking_out_does_not_ask() {
+        let dir = scratch_dir("keys-quit-after-clock-out");
+        let mut app = test_app(&dir);
+
+        press(&mut app, KeyCode::Char('c'));
+        add_log(&mut app, "sent with the clock out");
+        press(&mut app, KeyCode::Char('c'));
+        press(&mut app, KeyCode::Esc);
+
+        assert!(
+            app.handle_key(KeyEvent::from(KeyCode::Char('q')))
+                .is_break()
+        );
+        assert!(!app.confirm_quit);
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,67 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use chrono::{Datelike, Days, Local, NaiveDate};
+
+use crate::storage::{self, StorageError};
+
+/// Seconds worked per project, bucketed by the local date each session was clocked in on.
+pub struct ProjectTotals {
+    /// `None` keeps the totals in memory only, so an unreadable file is never overwritten.
+    path: Option<PathBuf>,
+    totals: BTreeMap<String, BTreeMap<NaiveDate, u64>>,
+}
+
+impl ProjectTotals {
+    pub fn load(path: PathBuf) -> Result<Self, StorageError> {
+        let totals = match storage::read(&path)? {
+            Some(contents) => serde_json::from_str(&contents)
+                .map_err(|error| StorageError::parse(&path, error))?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(ProjectTotals {
+            path: Some(path),
+            totals,
+        })
+    }
+
+    pub fn in_memory() -> Self {
+        ProjectTotals {
+            path: None,
+            totals: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        project: &str,
+        date: NaiveDate,
+        seconds: u64,
+    ) -> Result<(), StorageError> {
+        *self
+            .totals
+            .entry(project.to_string())
+            .or_default()
+            .entry(date)
+            .or_default() += seconds;
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let contents = serde_json::to_string_pretty(&self.totals)
+            .map_err(|error| StorageError::parse(path, error))?;
+
+        storage::write(path, &contents)
+    }
+
+    /// Seconds recorded for `project` since the most recent Monday.
+    pub fn this_week(&self, project: &str) -> u64 {
+        let today = Local::now().date_naive();
+        let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+
+        self.totals.get(project).map_or(0, |days| {
+            days.range(monday..=today).map(|(_, seconds)| seconds).sum()
+        })
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    error::Error,
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// A failure to read or write one of the files in the data directory.
+#[derive(Debug)]
+pub enum StorageError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl StorageError {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        StorageError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn parse(path: &Path, source: serde_json::Error) -> Self {
+        StorageError::Parse {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Io { path, source } => {
+                write!(f, "could not access {} ({})", path.display(), source)
+            }
+            StorageError::Parse { path, source } => {
+                write!(f, "could not parse {} ({})", path.display(), source)
+            }
+        }
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StorageError::Io { source, .. } => Some(source),
+            StorageError::Parse { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<StorageError> for io::Error {
+    fn from(error: StorageError) -> Self {
+        io::Error::other(error)
+    }
+}
+
+/// Reads `path`, treating a missing file as `None` rather than an error.
+pub fn read(path: &Path) -> Result<Option<String>, StorageError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(StorageError::io(path, error)),
+    }
+}
+
+/// Replaces the contents of `path`, creating its directory first if needed.
+pub fn write(path: &Path, contents: &str) -> Result<(), StorageError> {
+    create_parent(path)?;
+    fs::write(path, contents).map_err(|error| StorageError::io(path, error))
+}
+
+/// Appends `contents` to `path`, creating the file and its directory first if needed.
+pub fn append(path: &Path, contents: &str) -> Result<(), StorageError> {
+    append_bytes(path, contents.as_bytes())
+}
+
+/// Like `append`, for binary contents such as compressed archives.
+pub fn append_bytes(path: &Path, contents: &[u8]) -> Result<(), StorageError> {
+    create_parent(path)?;
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|error| StorageError::io(path, error))
+}
+
+fn create_parent(path: &Path) -> Result<(), StorageError> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent).map_err(|error| StorageError::io(parent, error)),
+        None => Ok(()),
+    }
+}
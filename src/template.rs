@@ -0,0 +1,84 @@
+/// Placeholders every message template can use.
+pub const PLACEHOLDERS: [&str; 5] = ["username", "date", "time", "total", "project"];
+
+/// Replaces each `{name}` in `template` with its value from `values`. Placeholders without a
+/// value are left as written.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+
+            values
+                .iter()
+                .find(|(placeholder, _)| *placeholder == name)
+                .map(|(_, value)| (*value, end))
+        });
+
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+/// The `{name}` placeholders in `template` that aren't in `PLACEHOLDERS`.
+pub fn unknown_placeholders(template: &str) -> Vec<&str> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .filter(|name| !PLACEHOLDERS.contains(name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_substitutes_every_occurrence() {
+        let values = [
+            ("username", "Ada"),
+            ("total", "2 Hours, 0 Minutes, 0 Seconds"),
+        ];
+
+        assert_eq!(
+            render("{username} is done! {username} worked {total}.", &values),
+            "Ada is done! Ada worked 2 Hours, 0 Minutes, 0 Seconds."
+        );
+        assert_eq!(render("no placeholders", &values), "no placeholders");
+    }
+
+    #[test]
+    fn render_leaves_unknown_and_unclosed_placeholders() {
+        let values = [("username", "Ada")];
+
+        assert_eq!(render("{mood} {username}", &values), "{mood} Ada");
+        assert_eq!(render("{username} {oops", &values), "Ada {oops");
+        assert_eq!(render("{{username}}", &values), "{Ada}");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_reported() {
+        assert!(unknown_placeholders("{username} clocked in on {project} at {time}").is_empty());
+        assert_eq!(
+            unknown_placeholders("{user} worked {total} {hours}"),
+            ["user", "hours"]
+        );
+    }
+}
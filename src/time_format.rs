@@ -0,0 +1,248 @@
+use serde::Deserialize;
+
+/// How durations are written out, both on screen and in webhook messages.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+    /// `02:14:03`
+    Clock,
+    /// `2 Hours, 14 Minutes, 3 Seconds`
+    Verbose,
+    /// `2h 14m`
+    Short,
+    /// `2.23h`, for timesheets
+    Decimal,
+}
+
+impl TimeFormat {
+    pub fn format(self, total: usize) -> String {
+        match self {
+            TimeFormat::Clock => format_compact(total),
+            TimeFormat::Verbose => format_verbose(total),
+            TimeFormat::Short => format_short(total),
+            TimeFormat::Decimal => format_decimal(total),
+        }
+    }
+}
+
+pub fn format_compact(total: usize) -> String {
+    let sec = total % 60;
+    let min = (total / 60) % 60;
+    let hr = (total / 3_600) % 24;
+    let days = total / 86_400;
+
+    if days > 0 {
+        format!("{}:{:02}:{:02}:{:02}", days, hr, min, sec)
+    } else if hr > 0 {
+        format!("{:02}:{:02}:{:02}", hr, min, sec)
+    } else if min > 0 {
+        format!("{:02}:{:02}", min, sec)
+    } else {
+        format!("{:02}", sec)
+    }
+}
+
+pub fn format_verbose(total: usize) -> String {
+    let sec = total % 60;
+    let min = (total / 60) % 60;
+    let hr = (total / 3_600) % 24;
+    let days = total / 86_400;
+
+    match (days, hr, min) {
+        (d, _, _) if d > 0 => {
+            format!("{} Days, {} Hours, {} Minutes, {} Seconds", d, hr, min, sec)
+        }
+        (_, h, _) if h > 0 => {
+            format!("{} Hours, {} Minutes, {} Seconds", h, min, sec)
+        }
+        (_, _, m) if m > 0 => {
+            format!("{} Minutes, {} Seconds", m, sec)
+        }
+        _ => {
+            format!("{} Seconds", sec)
+        }
+    }
+}
+
+fn format_short(total: usize) -> String {
+    let sec = total % 60;
+    let min = (total / 60) % 60;
+    let hr = total / 3_600;
+
+    if hr > 0 {
+        format!("{}h {}m", hr, min)
+    } else if min > 0 {
+        format!("{}m {}s", min, sec)
+    } else {
+        format!("{}s", sec)
+    }
+}
+
+fn format_decimal(total: usize) -> String {
+    format!("{:.2}h", total as f64 / 3_600.0)
+}
+
+/// How long ago something happened, e.g. `just now`, `3m ago` or `2h ago`.
+pub fn format_ago(secs: u64) -> String {
+    match secs {
+        0..10 => "just now".to_string(),
+        10..60 => format!("{}s ago", secs),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+/// Reads a duration written like `format_short` output without spaces, e.g. `45m`, `1h30m` or
+/// `90s`. Every number needs a unit, and the whole text must be used up. Durations too long to
+/// count in seconds are rejected.
+pub fn parse_short(text: &str) -> Option<usize> {
+    let mut total: usize = 0;
+    let mut rest = text;
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount = rest[..digits].parse::<usize>().ok()?;
+        let unit = match rest[digits..].chars().next()? {
+            'h' => 3_600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+
+        total = amount
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))?;
+        rest = &rest[digits + 1..];
+    }
+
+    Some(total)
+}
+
+/// Reads a correction to the elapsed time: a signed `parse_short` duration such as `+10m` or
+/// `-1h5m`, or a bare number of minutes. Unsigned amounts are added, and oversized ones
+/// rejected.
+pub fn parse_adjustment(text: &str) -> Option<i64> {
+    let text = text.trim();
+    let (sign, amount) = match text.strip_prefix('-') {
+        Some(amount) => (-1, amount),
+        None => (1, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    let secs = match amount.parse::<usize>() {
+        Ok(minutes) => minutes.checked_mul(60)?,
+        Err(_) => parse_short(amount)?,
+    };
+
+    Some(sign * i64::try_from(secs).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDARIES: [usize; 8] = [0, 59, 60, 3_599, 3_600, 8_043, 86_399, 90_061];
+
+    fn formatted(format: TimeFormat) -> Vec<String> {
+        BOUNDARIES
+            .iter()
+            .map(|&total| format.format(total))
+            .collect()
+    }
+
+    #[test]
+    fn clock_format() {
+        assert_eq!(
+            formatted(TimeFormat::Clock),
+            [
+                "00",
+                "59",
+                "01:00",
+                "59:59",
+                "01:00:00",
+                "02:14:03",
+                "23:59:59",
+                "1:01:01:01"
+            ]
+        );
+    }
+
+    #[test]
+    fn verbose_format() {
+        assert_eq!(
+            formatted(TimeFormat::Verbose),
+            [
+                "0 Seconds",
+                "59 Seconds",
+                "1 Minutes, 0 Seconds",
+                "59 Minutes, 59 Seconds",
+                "1 Hours, 0 Minutes, 0 Seconds",
+                "2 Hours, 14 Minutes, 3 Seconds",
+                "23 Hours, 59 Minutes, 59 Seconds",
+                "1 Days, 1 Hours, 1 Minutes, 1 Seconds"
+            ]
+        );
+    }
+
+    #[test]
+    fn short_format() {
+        assert_eq!(
+            formatted(TimeFormat::Short),
+            [
+                "0s", "59s", "1m 0s", "59m 59s", "1h 0m", "2h 14m", "23h 59m", "25h 1m"
+            ]
+        );
+    }
+
+    #[test]
+    fn ago_format() {
+        assert_eq!(
+            [0, 9, 10, 59, 60, 3_599, 3_600, 86_399, 86_400, 200_000].map(format_ago),
+            [
+                "just now", "just now", "10s ago", "59s ago", "1m ago", "59m ago", "1h ago",
+                "23h ago", "1d ago", "2d ago"
+            ]
+        );
+    }
+
+    #[test]
+    fn short_durations_parse() {
+        assert_eq!(parse_short("45m"), Some(2_700));
+        assert_eq!(parse_short("1h30m"), Some(5_400));
+        assert_eq!(parse_short("90s"), Some(90));
+        assert_eq!(parse_short("2h 14m"), None);
+        assert_eq!(parse_short("15"), None);
+        assert_eq!(parse_short("m"), None);
+        assert_eq!(parse_short("lunch"), None);
+        assert_eq!(parse_short(""), None);
+        assert_eq!(parse_short("999999999999999999h"), None);
+        assert_eq!(parse_short("18446744073709551615s1s"), None);
+    }
+
+    #[test]
+    fn adjustments_parse() {
+        assert_eq!(parse_adjustment("+10m"), Some(600));
+        assert_eq!(parse_adjustment("-1h5m"), Some(-3_900));
+        assert_eq!(parse_adjustment("15"), Some(900));
+        assert_eq!(parse_adjustment(" -5 "), Some(-300));
+        assert_eq!(parse_adjustment("+"), None);
+        assert_eq!(parse_adjustment("soon"), None);
+        assert_eq!(parse_adjustment("+999999999999999999h"), None);
+        assert_eq!(parse_adjustment("999999999999999999"), None);
+        assert_eq!(parse_adjustment("-18446744073709551615s"), None);
+    }
+
+    #[test]
+    fn decimal_format() {
+        assert_eq!(
+            formatted(TimeFormat::Decimal),
+            [
+                "0.00h", "0.02h", "0.02h", "1.00h", "1.00h", "2.23h", "24.00h", "25.02h"
+            ]
+        );
+    }
+}